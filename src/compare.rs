@@ -0,0 +1,142 @@
+// src/compare.rs
+
+//! Diffing two analysis runs, for "what changed this week" market updates.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::RunConfig;
+use crate::error::ParseError;
+use crate::parser::{AnalysisOutput, ItemAnalysis, analyze_trades, load_analysis};
+
+/// Median price moves larger than this percentage are flagged as significant.
+const SIGNIFICANT_PRICE_CHANGE_PERCENT: f64 = 10.0;
+
+/// What changed between an older analysis (A) and a newer one (B).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComparisonReport {
+    /// A's and B's `market_index`; a rise suggests overall inflation, a drop deflation.
+    pub market_index_before: Option<f64>,
+    pub market_index_after: Option<f64>,
+    /// Change relative to A's index; `None` when either index is missing or A's is zero.
+    pub market_index_change_percentage: Option<f64>,
+    /// Items reported in B but not in A, in B's order.
+    pub new_items: Vec<String>,
+    /// Items reported in A but no longer in B, in A's order.
+    pub removed_items: Vec<String>,
+    /// Items present in both runs, in B's order.
+    pub item_changes: Vec<ItemDelta>,
+}
+
+/// Per-item movement between the two runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ItemDelta {
+    pub item: String,
+    pub median_price_before: Option<f64>,
+    pub median_price_after: Option<f64>,
+    pub median_price_change: Option<f64>,
+    /// Change relative to the earlier median; `None` when that median is missing or zero.
+    pub median_price_change_percentage: Option<f64>,
+    /// True when the median moved by more than 10% either way.
+    pub significant_price_change: bool,
+    pub supply_posts_before: u32,
+    pub supply_posts_after: u32,
+    pub supply_posts_change: i64,
+    pub demand_posts_before: u32,
+    pub demand_posts_after: u32,
+    pub demand_posts_change: i64,
+}
+
+/// Diffs two analyses item by item, matching items by name.
+pub fn compare_outputs(a: &AnalysisOutput, b: &AnalysisOutput) -> ComparisonReport {
+    let before: HashMap<&str, &ItemAnalysis> = a
+        .items
+        .iter()
+        .map(|item| (item.item.as_str(), item))
+        .collect();
+    let after: HashMap<&str, &ItemAnalysis> = b
+        .items
+        .iter()
+        .map(|item| (item.item.as_str(), item))
+        .collect();
+
+    let mut new_items = Vec::new();
+    let mut item_changes = Vec::new();
+    for item in &b.items {
+        match before.get(item.item.as_str()) {
+            Some(old) => item_changes.push(item_delta(old, item)),
+            None => new_items.push(item.item.clone()),
+        }
+    }
+    let removed_items = a
+        .items
+        .iter()
+        .filter(|item| !after.contains_key(item.item.as_str()))
+        .map(|item| item.item.clone())
+        .collect();
+
+    ComparisonReport {
+        market_index_before: a.market_index,
+        market_index_after: b.market_index,
+        market_index_change_percentage: percentage_change(a.market_index, b.market_index),
+        new_items,
+        removed_items,
+        item_changes,
+    }
+}
+
+fn item_delta(old: &ItemAnalysis, new: &ItemAnalysis) -> ItemDelta {
+    let median_before = old.estimated_price.median;
+    let median_after = new.estimated_price.median;
+    let median_change = median_before
+        .zip(median_after)
+        .map(|(before, after)| after - before);
+    let median_change_percentage = percentage_change(median_before, median_after);
+    let old_counts = &old.supply_demand;
+    let new_counts = &new.supply_demand;
+    ItemDelta {
+        item: new.item.clone(),
+        median_price_before: median_before,
+        median_price_after: median_after,
+        median_price_change: median_change,
+        median_price_change_percentage: median_change_percentage,
+        significant_price_change: median_change_percentage
+            .is_some_and(|p| p.abs() > SIGNIFICANT_PRICE_CHANGE_PERCENT),
+        supply_posts_before: old_counts.supply_posts,
+        supply_posts_after: new_counts.supply_posts,
+        supply_posts_change: i64::from(new_counts.supply_posts)
+            - i64::from(old_counts.supply_posts),
+        demand_posts_before: old_counts.demand_posts,
+        demand_posts_after: new_counts.demand_posts,
+        demand_posts_change: i64::from(new_counts.demand_posts)
+            - i64::from(old_counts.demand_posts),
+    }
+}
+
+/// `after` relative to `before`, as a percentage; `None` when either is missing or `before` is zero.
+fn percentage_change(before: Option<f64>, after: Option<f64>) -> Option<f64> {
+    before
+        .zip(after)
+        .filter(|(before, _)| *before != 0.0)
+        .map(|(before, after)| (after - before) / before * 100.0)
+}
+
+/// Loads one side of a comparison: a `.csv` export is analyzed with `config`,
+/// anything else is read as a saved YAML (or JSON) analysis.
+pub fn load_comparison_input(path: &str, config: &RunConfig) -> Result<AnalysisOutput, ParseError> {
+    let is_csv = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    if is_csv {
+        let config = RunConfig {
+            file_paths: vec![path.to_string()],
+            ..config.clone()
+        };
+        return analyze_trades(&config);
+    }
+    if config.is_verbose {
+        eprintln!("Loading saved analysis from '{}'...", path);
+    }
+    load_analysis(path)
+}
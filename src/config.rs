@@ -0,0 +1,706 @@
+// src/config.rs
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use crate::error::ParseError;
+
+/// The serialization target for the final analysis output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Yaml,
+    Json,
+    JsonPretty,
+    Jsonl,
+    Markdown,
+    Csv,
+    Html,
+    Toml,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
+            "json" => Ok(OutputFormat::Json),
+            "json-pretty" => Ok(OutputFormat::JsonPretty),
+            "jsonl" | "ndjson" => Ok(OutputFormat::Jsonl),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "csv" => Ok(OutputFormat::Csv),
+            "html" => Ok(OutputFormat::Html),
+            "toml" => Ok(OutputFormat::Toml),
+            other => Err(format!(
+                "Unknown output format '{}'. Expected one of: yaml, json, json-pretty, jsonl, markdown, csv, html, toml.",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Json => "json",
+            OutputFormat::JsonPretty => "json-pretty",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Html => "html",
+            OutputFormat::Toml => "toml",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The primary key the output items are ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Median price.
+    Price,
+    /// Supply + demand posts.
+    Volume,
+    /// Supply + demand posts per day of the item's own trading span.
+    Frequency,
+    /// `liquidity_score`: volume times frequency.
+    Liquidity,
+    /// Item name, lexicographically.
+    Name,
+    /// Standard deviation of the item's prices.
+    Volatility,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "price" => Ok(SortKey::Price),
+            "volume" => Ok(SortKey::Volume),
+            "frequency" => Ok(SortKey::Frequency),
+            "liquidity" => Ok(SortKey::Liquidity),
+            "name" => Ok(SortKey::Name),
+            "volatility" => Ok(SortKey::Volatility),
+            other => Err(format!(
+                "Unknown sort key '{}'. Expected one of: price, volume, frequency, liquidity, name, volatility.",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SortKey::Price => "price",
+            SortKey::Volume => "volume",
+            SortKey::Frequency => "frequency",
+            SortKey::Liquidity => "liquidity",
+            SortKey::Name => "name",
+            SortKey::Volatility => "volatility",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How price tokens separate thousands from decimals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    /// `1,234.56`.
+    #[default]
+    Us,
+    /// `1.234,56`, as in German or French exports.
+    European,
+    /// Picked per run from the price tokens of the first records.
+    Auto,
+}
+
+impl FromStr for NumberFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "us" => Ok(NumberFormat::Us),
+            "european" | "eu" => Ok(NumberFormat::European),
+            "auto" => Ok(NumberFormat::Auto),
+            other => Err(format!(
+                "Unknown number format '{}'. Expected one of: us, european, auto.",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NumberFormat::Us => "us",
+            NumberFormat::European => "european",
+            NumberFormat::Auto => "auto",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How much an item's statistics can be trusted, by number of price observations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfidenceTier {
+    Insufficient,
+    Low,
+    Medium,
+    High,
+}
+
+impl FromStr for ConfidenceTier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "high" => Ok(ConfidenceTier::High),
+            "medium" => Ok(ConfidenceTier::Medium),
+            "low" => Ok(ConfidenceTier::Low),
+            "insufficient" => Ok(ConfidenceTier::Insufficient),
+            other => Err(format!(
+                "Unknown confidence tier '{}'. Expected one of: high, medium, low.",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ConfidenceTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfidenceTier::Insufficient => "insufficient",
+            ConfidenceTier::Low => "low",
+            ConfidenceTier::Medium => "medium",
+            ConfidenceTier::High => "high",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Options controlling a single analysis run.
+///
+/// Construct with `RunConfig::default()` and override only the fields you need:
+///
+/// ```
+/// use goskateapiparser::{OutputFormat, RunConfig};
+///
+/// let config = RunConfig {
+///     file_paths: vec!["src/data/tradexport_1755362248.csv".to_string()],
+///     output_format: OutputFormat::Json,
+///     ..RunConfig::default()
+/// };
+/// assert!(!config.is_verbose);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RunConfig {
+    /// CSV exports to merge into one analysis; none (or `"-"`) reads from stdin.
+    pub file_paths: Vec<String>,
+    /// Descend into subdirectories when a `file_paths` entry is a directory.
+    pub recursive: bool,
+    /// Memory-map input files instead of reading them through a buffer; stdin is
+    /// always read normally.
+    pub mmap: bool,
+    pub is_verbose: bool,
+    /// Write nothing to stderr but fatal errors; excludes `is_verbose`.
+    pub is_quiet: bool,
+    pub output_format: OutputFormat,
+    /// Earliest trade date (inclusive) to include in the analysis.
+    pub date_from: Option<NaiveDate>,
+    /// Latest trade date (inclusive) to include in the analysis.
+    pub date_to: Option<NaiveDate>,
+    /// Prices below this are dropped from the statistics as implausible.
+    pub min_price: f64,
+    /// Prices above this are dropped, such as a joking "I'll pay 99999999".
+    pub max_price: f64,
+    /// Fail on the first malformed CSV record or unparseable date instead of skipping it.
+    pub strict: bool,
+    /// Drop each item's prices outside 1.5 x IQR before computing statistics.
+    pub reject_outliers: bool,
+    /// Items with fewer supply + demand posts than this are left out of the output.
+    /// Defaults to 0 so items seen only in untagged posts are still reported.
+    pub min_posts_threshold: u32,
+    /// Minimum price observations for the `low`, `medium` and `high` confidence tiers;
+    /// anything below the first is `insufficient`.
+    pub confidence_thresholds: [usize; 3],
+    /// Items below this confidence tier are left out of the output.
+    pub min_confidence: Option<ConfidenceTier>,
+    /// chrono format string tried before the built-in date formats, e.g. `%d.%m.%Y %H:%M`.
+    pub date_format: Option<String>,
+    /// Exponential decay applied per day of age in the weighted median (0.01 halves
+    /// a price's weight after roughly 70 days).
+    pub decay_rate: f64,
+    /// Items last traded fewer than this many days ago are `"active"`.
+    pub active_threshold_days: f64,
+    /// Items last traded up to this many days ago are `"dormant"`; older ones are `"inactive"`.
+    pub dormant_threshold_days: f64,
+    /// Largest relative change between the older and newer half of an item's prices
+    /// still reported as a `"stable"` trend (0.05 = 5%).
+    pub trend_stable_threshold: f64,
+    /// Weight each supply or demand post by 1 + its Discord reaction count.
+    pub weight_reactions: bool,
+    /// An author repeating the same item at the same price within this many seconds
+    /// is treated as spam and skipped; 0 disables the check.
+    pub spam_window_secs: u64,
+    /// Bins in the per-item price histogram printed in verbose mode.
+    pub histogram_bins: usize,
+    /// Keep only the first `top` items after sorting.
+    pub top: Option<usize>,
+    /// Ordering of the output items; see [`RunConfig::sort_key`] for the default.
+    pub sort_by: Option<SortKey>,
+    /// Sort direction; see [`RunConfig::sort_ascending`] for the default.
+    pub sort_ascending: Option<bool>,
+    /// Build the per-author `authors` section; disabled by `--no-authors`.
+    pub include_authors: bool,
+    /// Attach every individual trade to each item as `price_history`.
+    pub include_history: bool,
+    /// Write only the global figures and standout items instead of every item.
+    pub summary: bool,
+    /// Attach a 24-hour post count to each item as `hourly_activity`.
+    pub include_hourly: bool,
+    /// Worker threads for record classification; rayon's default (one per core) when unset.
+    pub jobs: Option<usize>,
+    /// Where CSV output writes its metadata block; stderr when unset.
+    pub meta_output: Option<String>,
+    /// File the rendered output is written to; stdout when unset.
+    pub output_path: Option<String>,
+    /// Append to `output_path` instead of truncating it.
+    pub append_output: bool,
+    /// Directory that receives one YAML file per item plus a manifest, instead of `output_path`.
+    pub split_output: Option<String>,
+    /// Directory that receives one `{item}_history.csv` per item once the analysis
+    /// completes. The histories are left out of the main output unless
+    /// `include_history` is also set.
+    pub history_csv: Option<String>,
+    /// JSON or TOML file mapping item names to keywords, replacing the built-in item list.
+    pub items_file: Option<String>,
+    /// Maps variant item names to the canonical name their posts are counted under.
+    /// Resolved once, so an alias pointing at another alias is not followed.
+    pub item_aliases: HashMap<String, String>,
+    /// Replaces the built-in pattern that marks a post as selling.
+    pub sell_regex: Option<String>,
+    /// Replaces the built-in pattern that marks a post as buying.
+    pub buy_regex: Option<String>,
+    /// Replaces the built-in pattern that extracts price tokens.
+    pub price_regex: Option<String>,
+    /// Which of `.` and `,` is the decimal point in prices.
+    pub number_format: NumberFormat,
+    /// Prefixed to prices in human-readable output: a symbol such as `€`, or a
+    /// code like `USD` that maps to one. Empty for bare numbers.
+    pub currency: String,
+    /// Only records whose author name matches one of these patterns are analyzed;
+    /// every author passes when empty.
+    pub author_filters: Vec<String>,
+    /// Records whose author name matches any of these patterns are skipped.
+    pub author_excludes: Vec<String>,
+    /// Only items whose name matches one of these patterns are reported; every
+    /// item passes when empty.
+    pub item_filters: Vec<String>,
+    /// Items whose name matches any of these patterns are left out of the output.
+    pub item_excludes: Vec<String>,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        RunConfig {
+            file_paths: Vec::new(),
+            recursive: false,
+            mmap: false,
+            is_verbose: false,
+            is_quiet: false,
+            output_format: OutputFormat::default(),
+            date_from: None,
+            date_to: None,
+            min_price: 0.0,
+            max_price: f64::MAX,
+            strict: false,
+            reject_outliers: false,
+            min_posts_threshold: 0,
+            date_format: None,
+            confidence_thresholds: [2, 5, 20],
+            min_confidence: None,
+            decay_rate: 0.01,
+            active_threshold_days: 14.0,
+            dormant_threshold_days: 90.0,
+            trend_stable_threshold: 0.05,
+            weight_reactions: false,
+            spam_window_secs: 60,
+            histogram_bins: 10,
+            top: None,
+            sort_by: None,
+            sort_ascending: None,
+            include_authors: true,
+            include_history: false,
+            summary: false,
+            include_hourly: false,
+            jobs: None,
+            meta_output: None,
+            output_path: None,
+            append_output: false,
+            split_output: None,
+            history_csv: None,
+            items_file: None,
+            item_aliases: HashMap::new(),
+            sell_regex: None,
+            buy_regex: None,
+            price_regex: None,
+            number_format: NumberFormat::default(),
+            currency: String::new(),
+            author_filters: Vec::new(),
+            author_excludes: Vec::new(),
+            item_filters: Vec::new(),
+            item_excludes: Vec::new(),
+        }
+    }
+}
+
+impl RunConfig {
+    /// The effective sort order: `sort_by` when set, otherwise volume when `top`
+    /// picks the most traded items and median price in every other case.
+    pub fn sort_key(&self) -> SortKey {
+        match (self.sort_by, self.top) {
+            (Some(key), _) => key,
+            (None, Some(_)) => SortKey::Volume,
+            (None, None) => SortKey::Price,
+        }
+    }
+
+    /// The effective sort direction: `sort_ascending` when set, otherwise A to Z
+    /// for names and largest first for every numeric key.
+    pub fn sort_ascending(&self) -> bool {
+        self.sort_ascending
+            .unwrap_or(self.sort_key() == SortKey::Name)
+    }
+
+    /// The tier `observations` prices fall into under `confidence_thresholds`.
+    pub fn confidence_tier(&self, observations: usize) -> ConfidenceTier {
+        let [low, medium, high] = self.confidence_thresholds;
+        if observations >= high {
+            ConfidenceTier::High
+        } else if observations >= medium {
+            ConfidenceTier::Medium
+        } else if observations >= low {
+            ConfidenceTier::Low
+        } else {
+            ConfidenceTier::Insufficient
+        }
+    }
+}
+
+/// Settings loaded from a `--config` TOML file. Every key is optional; anything
+/// left out keeps its [`RunConfig`] default, and command-line flags override it.
+///
+/// ```toml
+/// file_paths = ["exports/"]
+/// output_format = "json-pretty"
+/// min_posts = 3
+/// date_from = "2025-07-01"
+/// sell_regex = '(?i)\b(sell|selling|wts|lf buyer)\b'
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// A single path is accepted as well as a list.
+    #[serde(
+        alias = "file_path",
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub file_paths: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recursive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mmap: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verbose: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quiet: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_from: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_to: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reject_outliers: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_posts: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence_thresholds: Option<[usize; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decay_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_threshold_days: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dormant_threshold_days: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trend_stable_threshold: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight_reactions: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spam_window_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub histogram_bins: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_ascending: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_authors: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_history: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_hourly: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta_output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub append: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history_csv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items_file: Option<String>,
+    /// `[item_aliases]` table of `"Variant Name" = "Canonical Name"` entries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_aliases: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sell_regex: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buy_regex: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_regex: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_filter: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_exclude: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_filter: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_exclude: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Reads and parses a TOML config file.
+    pub fn load(path: &str) -> Result<Config, ParseError> {
+        let text = fs::read_to_string(path)?;
+        Ok(toml::from_str(&text)?)
+    }
+
+    /// Overwrites every field of `config` that this file sets.
+    pub fn apply_to(self, config: &mut RunConfig) -> Result<(), ParseError> {
+        if let Some(file_paths) = self.file_paths {
+            config.file_paths = file_paths;
+        }
+        if let Some(format) = self.output_format {
+            config.output_format = format.parse().map_err(ParseError::InvalidArgument)?;
+        }
+        if let Some(date) = self.date_from {
+            config.date_from = Some(date);
+        }
+        if let Some(date) = self.date_to {
+            config.date_to = Some(date);
+        }
+        if let Some(top) = self.top {
+            config.top = Some(top);
+        }
+        if let Some(key) = self.sort_by {
+            config.sort_by = Some(key.parse().map_err(ParseError::InvalidArgument)?);
+        }
+        if let Some(format) = self.number_format {
+            config.number_format = format.parse().map_err(ParseError::InvalidArgument)?;
+        }
+        if let Some(currency) = self.currency {
+            config.currency = currency;
+        }
+        if let Some(ascending) = self.sort_ascending {
+            config.sort_ascending = Some(ascending);
+        }
+        if let Some(format) = self.date_format {
+            config.date_format = Some(format);
+        }
+        if let Some(thresholds) = self.confidence_thresholds {
+            if !thresholds.is_sorted() {
+                return Err(ParseError::InvalidArgument(format!(
+                    "confidence_thresholds must be in ascending order (low, medium, high), got {:?}.",
+                    thresholds
+                )));
+            }
+            config.confidence_thresholds = thresholds;
+        }
+        if let Some(tier) = self.min_confidence {
+            config.min_confidence = Some(tier.parse().map_err(ParseError::InvalidArgument)?);
+        }
+        if let Some(jobs) = self.jobs {
+            config.jobs = Some(jobs);
+        }
+        if let Some(path) = self.meta_output {
+            config.meta_output = Some(path);
+        }
+        if let Some(path) = self.output {
+            config.output_path = Some(path);
+        }
+        if let Some(dir) = self.split_output {
+            config.split_output = Some(dir);
+        }
+        if let Some(dir) = self.history_csv {
+            config.history_csv = Some(dir);
+        }
+        if let Some(path) = self.items_file {
+            config.items_file = Some(path);
+        }
+        if let Some(aliases) = self.item_aliases {
+            config.item_aliases = aliases;
+        }
+        if let Some(patterns) = self.author_filter {
+            config.author_filters = patterns;
+        }
+        if let Some(patterns) = self.author_exclude {
+            config.author_excludes = patterns;
+        }
+        if let Some(patterns) = self.item_filter {
+            config.item_filters = patterns;
+        }
+        if let Some(patterns) = self.item_exclude {
+            config.item_excludes = patterns;
+        }
+        config.recursive = self.recursive.unwrap_or(config.recursive);
+        config.mmap = self.mmap.unwrap_or(config.mmap);
+        config.is_verbose = self.verbose.unwrap_or(config.is_verbose);
+        config.is_quiet = self.quiet.unwrap_or(config.is_quiet);
+        if config.is_verbose && config.is_quiet {
+            return Err(ParseError::InvalidArgument(
+                "verbose and quiet cannot both be enabled.".to_string(),
+            ));
+        }
+        config.strict = self.strict.unwrap_or(config.strict);
+        config.min_price = self.min_price.unwrap_or(config.min_price);
+        config.max_price = self.max_price.unwrap_or(config.max_price);
+        config.reject_outliers = self.reject_outliers.unwrap_or(config.reject_outliers);
+        config.min_posts_threshold = self.min_posts.unwrap_or(config.min_posts_threshold);
+        config.decay_rate = self.decay_rate.unwrap_or(config.decay_rate);
+        config.active_threshold_days = self
+            .active_threshold_days
+            .unwrap_or(config.active_threshold_days);
+        config.dormant_threshold_days = self
+            .dormant_threshold_days
+            .unwrap_or(config.dormant_threshold_days);
+        config.trend_stable_threshold = self
+            .trend_stable_threshold
+            .unwrap_or(config.trend_stable_threshold);
+        config.weight_reactions = self.weight_reactions.unwrap_or(config.weight_reactions);
+        config.spam_window_secs = self.spam_window_secs.unwrap_or(config.spam_window_secs);
+        config.histogram_bins = self.histogram_bins.unwrap_or(config.histogram_bins);
+        config.include_authors = self.include_authors.unwrap_or(config.include_authors);
+        config.include_history = self.include_history.unwrap_or(config.include_history);
+        config.summary = self.summary.unwrap_or(config.summary);
+        config.include_hourly = self.include_hourly.unwrap_or(config.include_hourly);
+        config.append_output = self.append.unwrap_or(config.append_output);
+        config.sell_regex = self.sell_regex.or(config.sell_regex.take());
+        config.buy_regex = self.buy_regex.or(config.buy_regex.take());
+        config.price_regex = self.price_regex.or(config.price_regex.take());
+        Ok(())
+    }
+
+    /// Serializes the effective settings of `config` as TOML, for `--dump-config`.
+    pub fn dump(config: &RunConfig) -> Result<String, ParseError> {
+        let snapshot = Config {
+            file_paths: Some(config.file_paths.clone()),
+            recursive: Some(config.recursive),
+            mmap: Some(config.mmap),
+            verbose: Some(config.is_verbose),
+            quiet: Some(config.is_quiet),
+            output_format: Some(config.output_format.to_string()),
+            date_from: config.date_from,
+            date_to: config.date_to,
+            min_price: Some(config.min_price),
+            max_price: Some(config.max_price),
+            strict: Some(config.strict),
+            reject_outliers: Some(config.reject_outliers),
+            min_posts: Some(config.min_posts_threshold),
+            date_format: config.date_format.clone(),
+            confidence_thresholds: Some(config.confidence_thresholds),
+            min_confidence: config.min_confidence.map(|tier| tier.to_string()),
+            decay_rate: Some(config.decay_rate),
+            active_threshold_days: Some(config.active_threshold_days),
+            dormant_threshold_days: Some(config.dormant_threshold_days),
+            trend_stable_threshold: Some(config.trend_stable_threshold),
+            weight_reactions: Some(config.weight_reactions),
+            spam_window_secs: Some(config.spam_window_secs),
+            histogram_bins: Some(config.histogram_bins),
+            top: config.top,
+            sort_by: config.sort_by.map(|key| key.to_string()),
+            sort_ascending: config.sort_ascending,
+            include_authors: Some(config.include_authors),
+            include_history: Some(config.include_history),
+            summary: Some(config.summary),
+            include_hourly: Some(config.include_hourly),
+            jobs: config.jobs,
+            meta_output: config.meta_output.clone(),
+            output: config.output_path.clone(),
+            append: Some(config.append_output),
+            split_output: config.split_output.clone(),
+            history_csv: config.history_csv.clone(),
+            items_file: config.items_file.clone(),
+            item_aliases: Some(config.item_aliases.clone()).filter(|a| !a.is_empty()),
+            sell_regex: config.sell_regex.clone(),
+            buy_regex: config.buy_regex.clone(),
+            price_regex: config.price_regex.clone(),
+            number_format: Some(config.number_format.to_string()),
+            currency: Some(config.currency.clone()).filter(|c| !c.is_empty()),
+            author_filter: Some(config.author_filters.clone()).filter(|p| !p.is_empty()),
+            author_exclude: Some(config.author_excludes.clone()).filter(|p| !p.is_empty()),
+            item_filter: Some(config.item_filters.clone()).filter(|p| !p.is_empty()),
+            item_exclude: Some(config.item_excludes.clone()).filter(|p| !p.is_empty()),
+        };
+        Ok(toml::to_string(&snapshot)?)
+    }
+}
+
+/// Accepts either `"a.csv"` or `["a.csv", "b.csv"]`.
+fn one_or_many<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(Some(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(path) => vec![path],
+        OneOrMany::Many(paths) => paths,
+    }))
+}
@@ -0,0 +1,47 @@
+// src/display.rs
+
+//! Plain-text visualizations for verbose terminal output.
+
+/// Widest bar drawn for the fullest bin.
+const MAX_BAR_WIDTH: usize = 40;
+
+/// Buckets `prices` into `bins` equal-width bins between the lowest and highest
+/// price and draws one line per bin, with a `#` bar proportional to its count.
+/// Returns an empty string when there is nothing to draw.
+pub fn ascii_histogram(prices: &[f64], bins: usize) -> String {
+    if prices.is_empty() || bins == 0 {
+        return String::new();
+    }
+    let min = prices.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = prices.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    // Identical prices would give zero-width bins; draw them as a single bar.
+    let bins = if max > min { bins } else { 1 };
+    let width = (max - min) / bins as f64;
+
+    let mut counts = vec![0usize; bins];
+    for &price in prices {
+        let bin = if width > 0.0 {
+            (((price - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[bin] += 1;
+    }
+
+    let fullest = counts.iter().copied().max().unwrap_or(0).max(1);
+    let mut out = String::new();
+    for (i, count) in counts.iter().enumerate() {
+        let low = min + width * i as f64;
+        let high = if i + 1 == bins { max } else { low + width };
+        // Any non-empty bin gets at least one mark so it stays visible.
+        let bar_len = (count * MAX_BAR_WIDTH).div_ceil(fullest);
+        out.push_str(&format!(
+            "{:>12.2} - {:<12.2} |{} {}\n",
+            low,
+            high,
+            "#".repeat(bar_len),
+            count
+        ));
+    }
+    out
+}
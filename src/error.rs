@@ -0,0 +1,132 @@
+// src/error.rs
+
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+
+/// Everything that can go wrong while parsing, analyzing or rendering trade data.
+#[derive(Debug)]
+pub enum ParseError {
+    /// An input CSV could not be opened.
+    FileNotFound(io::Error),
+    /// The `--output` file could not be opened for writing.
+    OutputFileError(io::Error),
+    /// The `--split-output` or `--history-csv` directory (named first) could not be
+    /// created or written to.
+    OutputDirError(String, io::Error),
+    /// Any other I/O failure, such as reading a directory or writing a side file.
+    Io(io::Error),
+    CsvError(csv::Error),
+    YamlSerializationError(serde_yaml::Error),
+    JsonSerializationError(serde_json::Error),
+    /// A `--config` or `--items-file` TOML file is malformed or has unexpected keys.
+    ConfigError(toml::de::Error),
+    TomlSerializationError(toml::ser::Error),
+    /// The `--watch` file watcher could not be set up.
+    WatchError(notify::Error),
+    /// The `--serve` HTTP server could not listen on the given address.
+    ServerError(SocketAddr, io::Error),
+    /// The `--sqlite` database could not be opened, written or queried.
+    DatabaseError(rusqlite::Error),
+    /// A record's date matched no supported format, in `--strict` mode.
+    InvalidDate(String),
+    /// The input contained no usable trade records.
+    NoDataFound,
+    /// A caller-supplied option was invalid.
+    InvalidArgument(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::FileNotFound(e) => write!(f, "could not open input file: {}", e),
+            ParseError::OutputFileError(e) => write!(f, "could not open output file: {}", e),
+            ParseError::OutputDirError(dir, e) => {
+                write!(f, "could not write to output directory '{}': {}", dir, e)
+            }
+            ParseError::Io(e) => write!(f, "I/O error: {}", e),
+            ParseError::CsvError(e) => write!(f, "CSV error: {}", e),
+            ParseError::YamlSerializationError(e) => {
+                write!(f, "YAML (de)serialization failed: {}", e)
+            }
+            ParseError::JsonSerializationError(e) => write!(f, "JSON serialization failed: {}", e),
+            ParseError::ConfigError(e) => write!(f, "invalid config file: {}", e),
+            ParseError::TomlSerializationError(e) => write!(f, "TOML serialization failed: {}", e),
+            ParseError::WatchError(e) => write!(f, "file watcher failed: {}", e),
+            ParseError::ServerError(addr, e) => write!(f, "could not serve on {}: {}", addr, e),
+            ParseError::DatabaseError(e) => write!(f, "SQLite error: {}", e),
+            ParseError::InvalidDate(msg) => write!(f, "unparseable date: {}", msg),
+            ParseError::NoDataFound => write!(f, "no valid trade data found"),
+            ParseError::InvalidArgument(msg) => write!(f, "invalid argument: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::FileNotFound(e) | ParseError::OutputFileError(e) | ParseError::Io(e) => {
+                Some(e)
+            }
+            ParseError::OutputDirError(_, e) | ParseError::ServerError(_, e) => Some(e),
+            ParseError::CsvError(e) => Some(e),
+            ParseError::YamlSerializationError(e) => Some(e),
+            ParseError::JsonSerializationError(e) => Some(e),
+            ParseError::ConfigError(e) => Some(e),
+            ParseError::TomlSerializationError(e) => Some(e),
+            ParseError::WatchError(e) => Some(e),
+            ParseError::DatabaseError(e) => Some(e),
+            ParseError::InvalidDate(_)
+            | ParseError::NoDataFound
+            | ParseError::InvalidArgument(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+impl From<csv::Error> for ParseError {
+    fn from(e: csv::Error) -> Self {
+        ParseError::CsvError(e)
+    }
+}
+
+impl From<serde_yaml::Error> for ParseError {
+    fn from(e: serde_yaml::Error) -> Self {
+        ParseError::YamlSerializationError(e)
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(e: serde_json::Error) -> Self {
+        ParseError::JsonSerializationError(e)
+    }
+}
+
+impl From<toml::de::Error> for ParseError {
+    fn from(e: toml::de::Error) -> Self {
+        ParseError::ConfigError(e)
+    }
+}
+
+impl From<toml::ser::Error> for ParseError {
+    fn from(e: toml::ser::Error) -> Self {
+        ParseError::TomlSerializationError(e)
+    }
+}
+
+impl From<notify::Error> for ParseError {
+    fn from(e: notify::Error) -> Self {
+        ParseError::WatchError(e)
+    }
+}
+
+impl From<rusqlite::Error> for ParseError {
+    fn from(e: rusqlite::Error) -> Self {
+        ParseError::DatabaseError(e)
+    }
+}
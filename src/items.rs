@@ -1,103 +1,325 @@
+use aho_corasick::{AhoCorasick, MatchKind};
 use regex::{Regex, escape};
+use serde::Deserialize;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
 
-pub fn get_item_keywords() -> HashMap<String, Vec<Regex>> {
+use crate::error::ParseError;
+
+static ITEM_KEYWORDS: LazyLock<HashMap<String, ItemMatcher>> = LazyLock::new(|| {
+    compile_keywords(&builtin_keyword_table()).expect("built-in item keywords are valid")
+});
+
+/// The patterns that decide whether a post mentions one item.
+#[derive(Debug, Clone)]
+pub struct ItemMatcher {
+    /// Whole-word, case-insensitive keyword patterns; any one of them matching is a hit.
+    pub positive: Vec<Regex>,
+    /// Case-insensitive patterns for unrelated content that shares a keyword; any
+    /// one of them matching rejects the item even when a positive pattern matched.
+    pub negative: Vec<Regex>,
+}
+
+impl ItemMatcher {
+    /// True when `content` hits a positive pattern and no negative one.
+    pub fn is_match(&self, content: &str) -> bool {
+        self.positive.iter().any(|re| re.is_match(content)) && !self.rejects(content)
+    }
+
+    /// True when a negative pattern matches `content`.
+    pub fn rejects(&self, content: &str) -> bool {
+        self.negative.iter().any(|re| re.is_match(content))
+    }
+}
+
+/// Returns the item matchers, read from `override_path` when given and
+/// otherwise the built-in list, which is compiled once on first use and then
+/// borrowed, so repeated calls cost nothing.
+pub fn get_item_keywords(
+    override_path: Option<&str>,
+) -> Result<Cow<'static, HashMap<String, ItemMatcher>>, ParseError> {
+    match override_path {
+        Some(path) => compile_keywords(&load_keyword_table(path)?).map(Cow::Owned),
+        None => Ok(Cow::Borrowed(&ITEM_KEYWORDS)),
+    }
+}
+
+/// The keywords that identify an item and the negative patterns that veto them.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct ItemKeywords {
+    keywords: Vec<String>,
+    negative_patterns: Vec<String>,
+}
+
+/// One entry of an items file: a bare keyword list, or a table that adds
+/// negative patterns.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum KeywordEntry {
+    Keywords(Vec<String>),
+    Detailed {
+        keywords: Vec<String>,
+        #[serde(default)]
+        negative_patterns: Vec<String>,
+    },
+}
+
+impl From<KeywordEntry> for ItemKeywords {
+    fn from(entry: KeywordEntry) -> Self {
+        match entry {
+            KeywordEntry::Keywords(keywords) => ItemKeywords {
+                keywords,
+                negative_patterns: Vec::new(),
+            },
+            KeywordEntry::Detailed {
+                keywords,
+                negative_patterns,
+            } => ItemKeywords {
+                keywords,
+                negative_patterns,
+            },
+        }
+    }
+}
+
+/// Canonical item names paired with the keywords that identify them in a post.
+const ITEM_MAP_DATA: &[(&str, &[&str])] = &[
+    ("Moldady", &["Moldady"]),
+    ("Lowkey", &["Lowkey"]),
+    ("Buggy Basis", &["Buggy Basis"]),
+    ("Cherry Basics", &["Cherry Basics"]),
+    ("Hot Spring", &["Hot Spring"]),
+    ("Yellow Strip", &["Yellow Strip"]),
+    ("Buggy Riddles", &["Buggy Riddles"]),
+    ("Cherry Blades", &["Cherry Blades"]),
+    ("Green Camo", &["Green Camo"]),
+    ("Tiers", &["Tiers"]),
+    ("God Dog", &["God Dog"]),
+    ("Bug Queens", &["Bug Queens"]),
+    ("Cherry Swift", &["Cherry Swift"]),
+    ("Death Squad", &["Death Squad", "ds"]),
+    (
+        "Pink Tie Dye",
+        &["Pink Tie Dye", "pink tye dye", "pink dye"],
+    ),
+    ("Bug Websites", &["Bug Websites"]),
+    ("Cherry Blues", &["Cherry Blues"]),
+    ("Masked", &["Masked"]),
+    ("Immersed", &["Immersed"]),
+    ("Gradied", &["Gradied"]),
+    ("Acid", &["Acid"]),
+    ("Skilled", &["Skilled"]),
+    ("Paceid", &["Paceid"]),
+    ("Starced", &["Starced"]),
+    ("Bronzed", &["Bronzed"]),
+    ("Initiated", &["Initiated"]),
+    ("Violet Habita", &["Violet Habita"]),
+    ("Shrimpy", &["Shrimpy"]),
+    ("Thrown", &["Thrown"]),
+    ("Green Hollows", &["Green Hollows"]),
+    ("Pinkys", &["Pinkys"]),
+    ("Catz", &["Catz", "catz hollow"]),
+    ("Smooth", &["Smooth"]),
+    ("Purple Hollows", &["Purple Hollows", "purps"]),
+    ("Deads", &["Deads"]),
+    ("Pastel", &["Pastel"]),
+    ("Washed", &["Washed"]),
+    ("Red Hollows", &["Red Hollows"]),
+    ("Tunic", &["Tunic"]),
+    ("Leafy", &["Leafy"]),
+    ("The News", &["The News"]),
+    ("Dancing", &["Dancing"]),
+    ("Painted Rads", &["Painted Rads"]),
+    ("Blacks", &["Blacks"]),
+    ("Basic Cat", &["Basic Cat"]),
+    ("Blingy Star", &["Blingy Star"]),
+    ("Green Basic", &["Green Basic"]),
+    ("Compound", &["Compound"]),
+    ("Partners", &["Partners"]),
+    ("Mouth", &["Mouth"]),
+    ("Beard", &["Beard"]),
+    ("Stead", &["Stead"]),
+    ("Growll", &["Growll"]),
+    ("Starind", &["Starind"]),
+    ("Pringles", &["Pringles"]),
+    ("Hypnosis", &["Hypnosis"]),
+    ("Feather Dance", &["Feather Dance"]),
+    ("Amaranth", &["Amaranth"]),
+    ("Blue Flames", &["Blue Flames"]),
+    ("Faded", &["Faded"]),
+    ("Blue Devils", &["Blue Devils"]),
+    ("Wrapped Dance", &["Wrapped Dance"]),
+    ("Deadly", &["Deadly"]),
+    ("Diamonds", &["Diamonds"]),
+    ("Leaves", &["Leaves"]),
+    ("Amphibian", &["Amphibian"]),
+    ("Don Grill", &["Don Grill"]),
+    ("Marley", &["Marley"]),
+    ("Black is Mind", &["Black is Mind"]),
+    ("Kitty", &["Kitty"]),
+    ("Cinders", &["Cinders"]),
+    ("Piss", &["Piss"]),
+    ("Big Head", &["Big Head"]),
+    ("Creep", &["Creep"]),
+    ("Heal Lumps", &["Heal Lumps"]),
+    ("Star", &["Star"]),
+    ("Wings", &["Wings"]),
+    ("Smokey Lippes", &["Smokey Lippes"]),
+    ("Broski", &["Broski"]),
+    ("Barbed", &["Barbed"]),
+    ("Red Hollows", &["Red Hollows"]),
+    ("Black and Blue", &["Black and Blue"]),
+    ("Wicked", &["Wicked"]),
+];
+
+/// Regexes that veto a built-in item's keywords, for items whose keywords are
+/// everyday words in a trading chat.
+const NEGATIVE_PATTERN_DATA: &[(&str, &[&str])] = &[
+    ("Death Squad", &[r"\bnintendo\s+ds\b"]),
+    ("Smooth", &[r"\bsmooth(ly)?\s+(trade|deal|transaction)s?\b"]),
+];
+
+/// The item automaton and, per item, the negative patterns checked after a hit.
+#[derive(Debug, Clone)]
+pub struct ItemAutomaton {
+    /// All keywords, lowercased, for one-pass matching.
+    pub automaton: AhoCorasick,
+    /// The canonical item name of each automaton pattern, by pattern index.
+    pub names: Vec<String>,
+    /// Negative patterns by item name; items without any are absent.
+    pub negative: HashMap<String, Vec<Regex>>,
+}
+
+impl ItemAutomaton {
+    /// True when one of `item_name`'s negative patterns matches `content`.
+    pub fn rejects(&self, item_name: &str, content: &str) -> bool {
+        self.negative
+            .get(item_name)
+            .is_some_and(|patterns| patterns.iter().any(|re| re.is_match(content)))
+    }
+}
+
+/// Compiles every keyword into a single automaton for one-pass matching.
+/// Patterns are lowercase, so search lowercased content.
+pub fn build_aho_corasick_matcher(
+    override_path: Option<&str>,
+) -> Result<ItemAutomaton, ParseError> {
+    let table = match override_path {
+        Some(path) => load_keyword_table(path)?,
+        None => builtin_keyword_table(),
+    };
+    let mut patterns = Vec::new();
+    let mut names = Vec::new();
+    let mut negative = HashMap::new();
+    for (item_name, entry) in table {
+        for kw in &entry.keywords {
+            patterns.push(kw.to_lowercase());
+            names.push(item_name.clone());
+        }
+        let negative_patterns = compile_negative_patterns(&item_name, &entry.negative_patterns)?;
+        if !negative_patterns.is_empty() {
+            negative.insert(item_name, negative_patterns);
+        }
+    }
+    let automaton = AhoCorasick::builder()
+        .match_kind(MatchKind::Standard)
+        .build(&patterns)
+        .map_err(|e| ParseError::InvalidArgument(format!("Invalid item keywords: {}", e)))?;
+    Ok(ItemAutomaton {
+        automaton,
+        names,
+        negative,
+    })
+}
+
+fn builtin_keyword_table() -> Vec<(String, ItemKeywords)> {
+    let negative_patterns: HashMap<&str, &[&str]> = NEGATIVE_PATTERN_DATA.iter().copied().collect();
+    ITEM_MAP_DATA
+        .iter()
+        .map(|(item_name, keywords)| {
+            let entry = ItemKeywords {
+                keywords: keywords.iter().map(|kw| kw.to_string()).collect(),
+                negative_patterns: negative_patterns
+                    .get(item_name)
+                    .map_or_else(Vec::new, |patterns| {
+                        patterns.iter().map(|p| p.to_string()).collect()
+                    }),
+            };
+            (item_name.to_string(), entry)
+        })
+        .collect()
+}
+
+/// Reads an items file mapping each canonical item name to its keywords, or to a
+/// table that also lists negative patterns. Files ending in `.json` are parsed as
+/// JSON, anything else as TOML:
+///
+/// ```toml
+/// "Moldady" = ["Moldady", "mold"]
+/// "Hot Spring" = ["Hot Spring", "hotspring"]
+/// "Element" = { keywords = ["Element"], negative_patterns = ["elemental"] }
+/// ```
+fn load_keyword_table(path: &str) -> Result<Vec<(String, ItemKeywords)>, ParseError> {
+    let text = fs::read_to_string(path)?;
+    let map: HashMap<String, KeywordEntry> =
+        if Path::new(path).extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&text)?
+        } else {
+            toml::from_str(&text)?
+        };
+    let map: HashMap<String, ItemKeywords> = map
+        .into_iter()
+        .map(|(item_name, entry)| (item_name, entry.into()))
+        .collect();
+    // An empty keyword would match every post.
+    if let Some((item_name, _)) = map
+        .iter()
+        .find(|(_, entry)| entry.keywords.iter().any(|kw| kw.trim().is_empty()))
+    {
+        return Err(ParseError::InvalidArgument(format!(
+            "Items file '{}' has an empty keyword for '{}'",
+            path, item_name
+        )));
+    }
+    // Sorted so that matching does not depend on hash order.
+    let mut table: Vec<(String, ItemKeywords)> = map.into_iter().collect();
+    table.sort();
+    Ok(table)
+}
+
+fn compile_keywords(
+    table: &[(String, ItemKeywords)],
+) -> Result<HashMap<String, ItemMatcher>, ParseError> {
     let mut map = HashMap::new();
-    let item_map_data = [
-        ("Moldady", vec!["Moldady"]),
-        ("Lowkey", vec!["Lowkey"]),
-        ("Buggy Basis", vec!["Buggy Basis"]),
-        ("Cherry Basics", vec!["Cherry Basics"]),
-        ("Hot Spring", vec!["Hot Spring"]),
-        ("Yellow Strip", vec!["Yellow Strip"]),
-        ("Buggy Riddles", vec!["Buggy Riddles"]),
-        ("Cherry Blades", vec!["Cherry Blades"]),
-        ("Green Camo", vec!["Green Camo"]),
-        ("Tiers", vec!["Tiers"]),
-        ("God Dog", vec!["God Dog"]),
-        ("Bug Queens", vec!["Bug Queens"]),
-        ("Cherry Swift", vec!["Cherry Swift"]),
-        ("Death Squad", vec!["Death Squad", "ds"]),
-        (
-            "Pink Tie Dye",
-            vec!["Pink Tie Dye", "pink tye dye", "pink dye"],
-        ),
-        ("Bug Websites", vec!["Bug Websites"]),
-        ("Cherry Blues", vec!["Cherry Blues"]),
-        ("Masked", vec!["Masked"]),
-        ("Immersed", vec!["Immersed"]),
-        ("Gradied", vec!["Gradied"]),
-        ("Acid", vec!["Acid"]),
-        ("Skilled", vec!["Skilled"]),
-        ("Paceid", vec!["Paceid"]),
-        ("Starced", vec!["Starced"]),
-        ("Bronzed", vec!["Bronzed"]),
-        ("Initiated", vec!["Initiated"]),
-        ("Violet Habita", vec!["Violet Habita"]),
-        ("Shrimpy", vec!["Shrimpy"]),
-        ("Thrown", vec!["Thrown"]),
-        ("Green Hollows", vec!["Green Hollows"]),
-        ("Pinkys", vec!["Pinkys"]),
-        ("Catz", vec!["Catz", "catz hollow"]),
-        ("Smooth", vec!["Smooth"]),
-        ("Purple Hollows", vec!["Purple Hollows", "purps"]),
-        ("Deads", vec!["Deads"]),
-        ("Pastel", vec!["Pastel"]),
-        ("Washed", vec!["Washed"]),
-        ("Red Hollows", vec!["Red Hollows"]),
-        ("Tunic", vec!["Tunic"]),
-        ("Leafy", vec!["Leafy"]),
-        ("The News", vec!["The News"]),
-        ("Dancing", vec!["Dancing"]),
-        ("Painted Rads", vec!["Painted Rads"]),
-        ("Blacks", vec!["Blacks"]),
-        ("Basic Cat", vec!["Basic Cat"]),
-        ("Blingy Star", vec!["Blingy Star"]),
-        ("Green Basic", vec!["Green Basic"]),
-        ("Compound", vec!["Compound"]),
-        ("Partners", vec!["Partners"]),
-        ("Mouth", vec!["Mouth"]),
-        ("Beard", vec!["Beard"]),
-        ("Stead", vec!["Stead"]),
-        ("Growll", vec!["Growll"]),
-        ("Starind", vec!["Starind"]),
-        ("Pringles", vec!["Pringles"]),
-        ("Hypnosis", vec!["Hypnosis"]),
-        ("Feather Dance", vec!["Feather Dance"]),
-        ("Amaranth", vec!["Amaranth"]),
-        ("Blue Flames", vec!["Blue Flames"]),
-        ("Faded", vec!["Faded"]),
-        ("Blue Devils", vec!["Blue Devils"]),
-        ("Wrapped Dance", vec!["Wrapped Dance"]),
-        ("Deadly", vec!["Deadly"]),
-        ("Diamonds", vec!["Diamonds"]),
-        ("Leaves", vec!["Leaves"]),
-        ("Amphibian", vec!["Amphibian"]),
-        ("Don Grill", vec!["Don Grill"]),
-        ("Marley", vec!["Marley"]),
-        ("Black is Mind", vec!["Black is Mind"]),
-        ("Kitty", vec!["Kitty"]),
-        ("Cinders", vec!["Cinders"]),
-        ("Piss", vec!["Piss"]),
-        ("Big Head", vec!["Big Head"]),
-        ("Creep", vec!["Creep"]),
-        ("Heal Lumps", vec!["Heal Lumps"]),
-        ("Star", vec!["Star"]),
-        ("Wings", vec!["Wings"]),
-        ("Smokey Lippes", vec!["Smokey Lippes"]),
-        ("Broski", vec!["Broski"]),
-        ("Barbed", vec!["Barbed"]),
-        ("Red Hollows", vec!["Red Hollows"]),
-        ("Black and Blue", vec!["Black and Blue"]),
-        ("Wicked", vec!["Wicked"]),
-    ];
-
-    for (item_name, keywords) in item_map_data {
-        let regexes: Vec<Regex> = keywords
-            .into_iter()
+    for (item_name, entry) in table {
+        let positive: Vec<Regex> = entry
+            .keywords
+            .iter()
             .map(|kw| Regex::new(&format!(r"(?i)\b{}\b", escape(kw))).unwrap())
             .collect();
-        map.insert(item_name.to_string(), regexes);
+        let negative = compile_negative_patterns(item_name, &entry.negative_patterns)?;
+        map.insert(item_name.clone(), ItemMatcher { positive, negative });
     }
-    map
+    Ok(map)
+}
+
+/// Negative patterns are regexes matched case-insensitively anywhere in the post.
+fn compile_negative_patterns(
+    item_name: &str,
+    patterns: &[String],
+) -> Result<Vec<Regex>, ParseError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(&format!("(?i){}", pattern)).map_err(|e| {
+                ParseError::InvalidArgument(format!(
+                    "Invalid negative pattern '{}' for '{}': {}",
+                    pattern, item_name, e
+                ))
+            })
+        })
+        .collect()
 }
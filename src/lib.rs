@@ -0,0 +1,48 @@
+// src/lib.rs
+
+//! Library entry point for the Go Skate trade data parser.
+//!
+//! The binary is a thin CLI wrapper around [`run_trade_analysis`]; downstream
+//! crates can call the same functions directly and work with the returned
+//! [`AnalysisOutput`] instead of parsing serialized text.
+
+pub mod compare;
+pub mod config;
+pub mod display;
+pub mod error;
+pub mod items;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+pub mod output;
+pub mod parser;
+pub mod price_parser;
+pub mod serve;
+pub mod sqlite;
+pub mod stats;
+pub mod summary;
+pub mod types;
+pub mod validate;
+pub mod watch;
+
+pub use compare::{ComparisonReport, ItemDelta, compare_outputs, load_comparison_input};
+pub use config::{ConfidenceTier, Config, NumberFormat, OutputFormat, RunConfig, SortKey};
+pub use error::ParseError;
+#[cfg(feature = "async")]
+pub use nonblocking::run_trade_analysis_async;
+pub use output::{
+    render_comparison, render_output, render_query_result, write_history_csv, write_split_output,
+};
+pub use parser::{
+    AnalysisMetadata, AnalysisOutput, AuthorAnalysis, EstimatedPrice, InputFileSummary,
+    ItemAnalysis, ItemStats, PricePoint, SupplyDemand, TradeChance, TradeParser, TradeRecord,
+    analyze_trades, load_analysis, merge_outputs, run_trade_analysis, run_trade_analysis_to,
+    write_analysis_to,
+};
+pub use price_parser::PriceParser;
+pub use serve::serve_trade_analysis;
+pub use sqlite::{QueryResult, query_sqlite, save_to_sqlite};
+pub use stats::price_at_percentile;
+pub use summary::{AnalysisSummary, summarize};
+pub use types::{AuthorId, ItemName, Price};
+pub use validate::{RowError, ValidationReport, validate_csv};
+pub use watch::watch_trade_analysis;
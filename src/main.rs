@@ -1,35 +1,639 @@
 // src/main.rs
 
-mod items;
-mod parser;
-
+use chrono::NaiveDate;
+use goskateapiparser::{
+    AnalysisOutput, Config, ParseError, RunConfig, analyze_trades, compare_outputs, load_analysis,
+    load_comparison_input, merge_outputs, price_at_percentile, query_sqlite, render_comparison,
+    render_output, render_query_result, run_trade_analysis_to, save_to_sqlite,
+    serve_trade_analysis, validate_csv, watch_trade_analysis, write_analysis_to,
+};
 use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+/// Parses a `YYYY-MM-DD` value given to `flag`.
+fn parse_date_flag(flag: &str, value: Option<&String>) -> Result<NaiveDate, ParseError> {
+    let Some(value) = value else {
+        return Err(ParseError::InvalidArgument(format!(
+            "{} flag requires a date in YYYY-MM-DD format.",
+            flag
+        )));
+    };
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|e| {
+        ParseError::InvalidArgument(format!("Invalid date '{}' for {}: {}", value, flag, e))
+    })
+}
+
+/// Parses a numeric value given to `flag`.
+fn parse_number_flag<T>(flag: &str, value: Option<&String>) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let Some(value) = value else {
+        return Err(ParseError::InvalidArgument(format!(
+            "{} flag requires a number.",
+            flag
+        )));
+    };
+    value.parse().map_err(|e| {
+        ParseError::InvalidArgument(format!("Invalid number '{}' for {}: {}", value, flag, e))
+    })
+}
+
+/// Opens `path` for writing, creating it if needed and truncating it unless `append` is set.
+fn open_output_file(path: &str, append: bool) -> Result<File, ParseError> {
+    let mut options = OpenOptions::new();
+    options.create(true);
+    if append {
+        options.append(true);
+    } else {
+        options.write(true).truncate(true);
+    }
+    options.open(path).map_err(ParseError::OutputFileError)
+}
+
+fn quiet_verbose_conflict() -> ParseError {
+    ParseError::InvalidArgument("--quiet and --verbose cannot be used together.".to_string())
+}
+
+/// Prints what a `--dry-run` found, failing with [`ParseError::NoDataFound`] when no
+/// record became a trade observation so scripts can check the exit code.
+fn print_dry_run_summary(analysis: &AnalysisOutput) -> Result<(), ParseError> {
+    let metadata = &analysis.metadata;
+    let records: u32 = metadata.input_files.iter().map(|f| f.records).sum();
+    let skipped = metadata.skipped_records_count
+        + metadata.out_of_range_records_count
+        + metadata.duplicate_records_count
+        + metadata.spam_filtered_count
+        + metadata.author_filtered_count;
+    println!("Dry run complete; no output was written.");
+    println!("Records processed: {}", records);
+    println!("Records skipped: {}", skipped);
+    println!("Trade observations: {}", metadata.observations_count);
+    println!("Items found: {}", analysis.items.len());
+    println!("Parsing time: {} ms", analysis.total_parsing_time_ms);
+    if metadata.observations_count == 0 {
+        return Err(ParseError::NoDataFound);
+    }
+    Ok(())
+}
+
+/// Runs the analysis `iterations` times without rendering it and reports the
+/// wall-clock times to stderr, as YAML so timing scripts can parse it.
+fn run_benchmark(config: &RunConfig, iterations: usize) -> Result<(), ParseError> {
+    // Progress bars and warnings would be repeated on every run.
+    let config = RunConfig {
+        is_quiet: true,
+        is_verbose: false,
+        ..config.clone()
+    };
+    let mut timings_ms = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        analyze_trades(&config)?;
+        timings_ms.push(start.elapsed().as_secs_f64() * 1000.0);
+    }
+    timings_ms.sort_by(f64::total_cmp);
+    let n = timings_ms.len();
+    let median = price_at_percentile(&timings_ms, 50.0).unwrap_or_default();
+    // Nearest-rank percentile, so small runs report their slowest iteration.
+    let p99 = timings_ms[(n * 99).div_ceil(100) - 1];
+    eprintln!("benchmark:");
+    eprintln!("  iterations: {}", n);
+    eprintln!(
+        "  mean_ms: {:.3}",
+        timings_ms.iter().sum::<f64>() / n as f64
+    );
+    eprintln!("  median_ms: {:.3}", median);
+    eprintln!("  min_ms: {:.3}", timings_ms[0]);
+    eprintln!("  max_ms: {:.3}", timings_ms[n - 1]);
+    eprintln!("  p99_ms: {:.3}", p99);
+    Ok(())
+}
+
+/// Turns a failure into the message shown to the user.
+fn describe_error(error: &ParseError) -> String {
+    match error {
+        ParseError::FileNotFound(e) => format!(
+            "Could not open the input file: {}. Check the --data path, or omit it (or pass '-') to read from stdin.",
+            e
+        ),
+        ParseError::OutputFileError(e) => {
+            format!("Could not open the --output file for writing: {}", e)
+        }
+        ParseError::OutputDirError(dir, e) => format!(
+            "Could not create or write to the --split-output or --history-csv directory '{}': {}",
+            dir, e
+        ),
+        ParseError::Io(e) => format!("Could not read or write a file: {}", e),
+        ParseError::CsvError(e) => {
+            format!("The input is not a valid Discord chat export CSV: {}", e)
+        }
+        ParseError::YamlSerializationError(e) => format!("Could not read or write YAML: {}", e),
+        ParseError::JsonSerializationError(e) => format!("Could not write JSON output: {}", e),
+        ParseError::ConfigError(e) => format!("The config file is invalid: {}", e),
+        ParseError::TomlSerializationError(e) => format!("Could not write TOML output: {}", e),
+        ParseError::WatchError(e) => format!("Could not watch the input for changes: {}", e),
+        ParseError::ServerError(addr, e) => {
+            format!("Could not start the --serve HTTP server on {}: {}", addr, e)
+        }
+        ParseError::DatabaseError(e) => {
+            format!(
+                "Could not open, write or query the --sqlite database: {}",
+                e
+            )
+        }
+        ParseError::InvalidDate(msg) => format!("Found an unparseable date (--strict): {}", msg),
+        ParseError::NoDataFound => "No valid trade data was found in the input.".to_string(),
+        ParseError::InvalidArgument(msg) => msg.clone(),
+    }
+}
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> ExitCode {
     let args: Vec<String> = env::args().collect();
-    let mut is_verbose = false;
-    let mut file_path = "".to_string(); // Default file path
+    let result = match args.get(1).map(String::as_str) {
+        Some("validate" | "--validate-csv") => run_validate(&args[2..]),
+        _ => run().map(|()| ExitCode::SUCCESS),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {}", describe_error(&e));
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    // Iterate through arguments to find flags and their values
+/// `validate FILE... [--date-format FMT]`: checks each file's header and first
+/// rows, printing a pass/fail line per file. Fails if any file does.
+fn run_validate(args: &[String]) -> Result<ExitCode, ParseError> {
+    let mut paths = Vec::new();
+    let mut date_format: Option<String> = None;
     let mut i = 0;
     while i < args.len() {
-        if args[i] == "-v" || args[i] == "--verbose" {
-            is_verbose = true;
+        if args[i] == "--date-format" {
+            let Some(format) = args.get(i + 1) else {
+                return Err(ParseError::InvalidArgument(
+                    "--date-format flag requires a chrono format string.".to_string(),
+                ));
+            };
+            date_format = Some(format.clone());
+            i += 1; // Skip the next argument as it's the format string
+        } else {
+            paths.push(args[i].clone());
+        }
+        i += 1;
+    }
+    if paths.is_empty() {
+        return Err(ParseError::InvalidArgument(
+            "validate requires at least one CSV file.".to_string(),
+        ));
+    }
+
+    let mut all_valid = true;
+    for path in &paths {
+        let report = validate_csv(path, date_format.as_deref())?;
+        if report.is_valid() {
+            println!("PASS '{}' ({} rows checked)", path, report.rows_checked);
+            continue;
+        }
+        all_valid = false;
+        println!("FAIL '{}'", path);
+        if !report.missing_columns.is_empty() {
+            println!("  Missing columns: {}", report.missing_columns.join(", "));
+        }
+        for error in &report.row_errors {
+            println!("  Line {}: {}", error.line, error.message);
+        }
+        if !report.row_errors.is_empty() {
+            println!(
+                "  {} of {} rows checked have errors.",
+                report.row_errors.len(),
+                report.rows_checked
+            );
+        }
+    }
+    Ok(if all_valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn run() -> Result<(), ParseError> {
+    let args: Vec<String> = env::args().collect();
+    let mut config = RunConfig::default();
+
+    // The config file is the base layer, so load it before any other flag is applied.
+    if let Some(pos) = args.iter().position(|arg| arg == "--config") {
+        let Some(path) = args.get(pos + 1) else {
+            return Err(ParseError::InvalidArgument(
+                "--config flag requires a file path.".to_string(),
+            ));
+        };
+        Config::load(path)?.apply_to(&mut config)?;
+    }
+    let mut cli_paths: Vec<String> = Vec::new();
+    let mut cli_author_filters: Vec<String> = Vec::new();
+    let mut cli_author_excludes: Vec<String> = Vec::new();
+    let mut cli_item_filters: Vec<String> = Vec::new();
+    let mut cli_item_excludes: Vec<String> = Vec::new();
+    let mut dump_config = false;
+    let mut dry_run = false;
+    let mut benchmark: Option<usize> = None;
+    let mut compare: Option<(String, String)> = None;
+    // `merge A B` must come first; the files are read before any other flag.
+    let merge = if args.get(1).is_some_and(|arg| arg == "merge") {
+        let (Some(a), Some(b)) = (args.get(2), args.get(3)) else {
+            return Err(ParseError::InvalidArgument(
+                "merge requires two saved analyses: merge FILE_A FILE_B.".to_string(),
+            ));
+        };
+        Some((a.clone(), b.clone()))
+    } else {
+        None
+    };
+    let mut watch = false;
+    let mut serve: Option<u16> = None;
+    let mut serve_bind: Option<IpAddr> = None;
+    let mut sqlite: Option<String> = None;
+    let mut sqlite_query: Option<String> = None;
+    let mut cli_verbose = false;
+    let mut cli_quiet = false;
+
+    // Iterate through arguments to find flags and their values
+    let mut i = if merge.is_some() { 4 } else { 0 };
+    while i < args.len() {
+        if args[i] == "--config" {
+            i += 1; // Skip the next argument as it's the already loaded config path
+        } else if args[i] == "--compare" {
+            let (Some(a), Some(b)) = (args.get(i + 1), args.get(i + 2)) else {
+                return Err(ParseError::InvalidArgument(
+                    "--compare flag requires two files: FILE_A FILE_B.".to_string(),
+                ));
+            };
+            compare = Some((a.clone(), b.clone()));
+            i += 2; // Skip the next two arguments as they're the files to compare
+        } else if args[i] == "--watch" {
+            watch = true;
+        } else if args[i] == "--serve" {
+            serve = Some(parse_number_flag("--serve", args.get(i + 1))?);
+            i += 1; // Skip the next argument as it's the port
+        } else if args[i] == "--serve-bind" {
+            let Some(addr) = args.get(i + 1) else {
+                return Err(ParseError::InvalidArgument(
+                    "--serve-bind flag requires an IP address, such as 0.0.0.0.".to_string(),
+                ));
+            };
+            serve_bind = Some(addr.parse().map_err(|e| {
+                ParseError::InvalidArgument(format!(
+                    "Invalid address '{}' for --serve-bind: {}",
+                    addr, e
+                ))
+            })?);
+            i += 1; // Skip the next argument as it's the address
+        } else if args[i] == "--sqlite" {
+            if let Some(path) = args.get(i + 1) {
+                sqlite = Some(path.clone());
+                i += 1; // Skip the next argument as it's the database path
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--sqlite flag requires a database path.".to_string(),
+                ));
+            }
+        } else if args[i] == "--sqlite-query" {
+            if let Some(sql) = args.get(i + 1) {
+                sqlite_query = Some(sql.clone());
+                i += 1; // Skip the next argument as it's the SQL statement
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--sqlite-query flag requires an SQL statement.".to_string(),
+                ));
+            }
+        } else if args[i] == "--dump-config" {
+            dump_config = true;
+        } else if args[i] == "--dry-run" {
+            dry_run = true;
+        } else if args[i] == "--benchmark" {
+            let iterations = parse_number_flag("--benchmark", args.get(i + 1))?;
+            if iterations == 0 {
+                return Err(ParseError::InvalidArgument(
+                    "--benchmark needs at least one iteration.".to_string(),
+                ));
+            }
+            benchmark = Some(iterations);
+            i += 1; // Skip the next argument as it's the iteration count
+        } else if args[i] == "-v" || args[i] == "--verbose" {
+            if cli_quiet {
+                return Err(quiet_verbose_conflict());
+            }
+            cli_verbose = true;
+            config.is_verbose = true;
+            config.is_quiet = false;
+        } else if args[i] == "-q" || args[i] == "--quiet" {
+            if cli_verbose {
+                return Err(quiet_verbose_conflict());
+            }
+            cli_quiet = true;
+            config.is_quiet = true;
+            config.is_verbose = false;
+        } else if args[i] == "--min-posts" {
+            config.min_posts_threshold = parse_number_flag("--min-posts", args.get(i + 1))?;
+            i += 1; // Skip the next argument as it's the threshold
+        } else if args[i] == "--min-confidence" {
+            if let Some(tier) = args.get(i + 1) {
+                config.min_confidence = Some(tier.parse().map_err(ParseError::InvalidArgument)?);
+                i += 1; // Skip the next argument as it's the tier name
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--min-confidence flag requires a tier: high, medium or low.".to_string(),
+                ));
+            }
+        } else if args[i] == "--min-price" {
+            config.min_price = parse_number_flag("--min-price", args.get(i + 1))?;
+            i += 1; // Skip the next argument as it's the price
+        } else if args[i] == "--max-price" {
+            config.max_price = parse_number_flag("--max-price", args.get(i + 1))?;
+            i += 1; // Skip the next argument as it's the price
+        } else if args[i] == "--decay-rate" {
+            config.decay_rate = parse_number_flag("--decay-rate", args.get(i + 1))?;
+            i += 1; // Skip the next argument as it's the decay rate
+        } else if args[i] == "-j" || args[i] == "--jobs" {
+            config.jobs = Some(parse_number_flag("--jobs", args.get(i + 1))?);
+            i += 1; // Skip the next argument as it's the thread count
+        } else if args[i] == "--spam-window-secs" {
+            config.spam_window_secs = parse_number_flag("--spam-window-secs", args.get(i + 1))?;
+            i += 1; // Skip the next argument as it's the window length
+        } else if args[i] == "--histogram-bins" {
+            config.histogram_bins = parse_number_flag("--histogram-bins", args.get(i + 1))?;
+            i += 1; // Skip the next argument as it's the bin count
+        } else if args[i] == "--top" {
+            config.top = Some(parse_number_flag("--top", args.get(i + 1))?);
+            i += 1; // Skip the next argument as it's the item count
+        } else if args[i] == "--sort-by" {
+            if let Some(key) = args.get(i + 1) {
+                config.sort_by = Some(key.parse().map_err(ParseError::InvalidArgument)?);
+                i += 1; // Skip the next argument as it's the sort key
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--sort-by flag requires a sort key.".to_string(),
+                ));
+            }
+        } else if args[i] == "--number-format" {
+            if let Some(format) = args.get(i + 1) {
+                config.number_format = format.parse().map_err(ParseError::InvalidArgument)?;
+                i += 1; // Skip the next argument as it's the number format
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--number-format flag requires a format.".to_string(),
+                ));
+            }
+        } else if args[i] == "--currency" {
+            if let Some(currency) = args.get(i + 1) {
+                config.currency = currency.clone();
+                i += 1; // Skip the next argument as it's the currency
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--currency flag requires a symbol or code.".to_string(),
+                ));
+            }
+        } else if args[i] == "--sort-asc" {
+            config.sort_ascending = Some(true);
+        } else if args[i] == "--sort-desc" {
+            config.sort_ascending = Some(false);
+        } else if args[i] == "--no-authors" {
+            config.include_authors = false;
+        } else if args[i] == "--include-history" {
+            config.include_history = true;
+        } else if args[i] == "--summary" {
+            config.summary = true;
+        } else if args[i] == "--include-hourly" {
+            config.include_hourly = true;
+        } else if args[i] == "--weight-reactions" {
+            config.weight_reactions = true;
+        } else if args[i] == "--strict" {
+            config.strict = true;
+        } else if args[i] == "--reject-outliers" {
+            config.reject_outliers = true;
+        } else if args[i] == "-r" || args[i] == "--recursive" {
+            config.recursive = true;
+        } else if args[i] == "--mmap" {
+            config.mmap = true;
         } else if args[i] == "-d" || args[i] == "--data" {
-            // Check if there's a next argument for the file path
+            // Check if there's a next argument for the file path(s)
+            if let Some(paths) = args.get(i + 1) {
+                cli_paths.extend(
+                    paths
+                        .split(',')
+                        .filter(|p| !p.is_empty())
+                        .map(str::to_string),
+                );
+                i += 1; // Skip the next argument as it's the file path list
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "-d or --data flag requires a file path.".to_string(),
+                ));
+            }
+        } else if args[i] == "-f" || args[i] == "--format" {
+            if let Some(format) = args.get(i + 1) {
+                config.output_format = format.parse().map_err(ParseError::InvalidArgument)?;
+                i += 1; // Skip the next argument as it's the format name
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "-f or --format flag requires a format name.".to_string(),
+                ));
+            }
+        } else if args[i] == "--meta-output" {
+            if let Some(path) = args.get(i + 1) {
+                config.meta_output = Some(path.clone());
+                i += 1; // Skip the next argument as it's the file path
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--meta-output flag requires a file path.".to_string(),
+                ));
+            }
+        } else if args[i] == "-o" || args[i] == "--output" {
             if let Some(path) = args.get(i + 1) {
-                file_path = path.clone();
+                config.output_path = Some(path.clone());
                 i += 1; // Skip the next argument as it's the file path
             } else {
-                eprintln!("Error: -d or --data flag requires a file path.");
-                return Err("Missing file path for -d flag".into());
+                return Err(ParseError::InvalidArgument(
+                    "-o or --output flag requires a file path.".to_string(),
+                ));
+            }
+        } else if args[i] == "--split-output" {
+            if let Some(dir) = args.get(i + 1) {
+                config.split_output = Some(dir.clone());
+                i += 1; // Skip the next argument as it's the directory
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--split-output flag requires a directory path.".to_string(),
+                ));
+            }
+        } else if args[i] == "--history-csv" {
+            if let Some(dir) = args.get(i + 1) {
+                config.history_csv = Some(dir.clone());
+                i += 1; // Skip the next argument as it's the directory
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--history-csv flag requires a directory path.".to_string(),
+                ));
             }
+        } else if args[i] == "--append" {
+            config.append_output = true;
+        } else if args[i] == "--date-format" {
+            if let Some(format) = args.get(i + 1) {
+                config.date_format = Some(format.clone());
+                i += 1; // Skip the next argument as it's the format string
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--date-format flag requires a chrono format string.".to_string(),
+                ));
+            }
+        } else if matches!(
+            args[i].as_str(),
+            "--author-filter" | "--author-exclude" | "--item-filter" | "--item-exclude"
+        ) {
+            if let Some(pattern) = args.get(i + 1) {
+                let patterns = match args[i].as_str() {
+                    "--author-filter" => &mut cli_author_filters,
+                    "--author-exclude" => &mut cli_author_excludes,
+                    "--item-filter" => &mut cli_item_filters,
+                    _ => &mut cli_item_excludes,
+                };
+                patterns.push(pattern.clone());
+                i += 1; // Skip the next argument as it's the pattern
+            } else {
+                return Err(ParseError::InvalidArgument(format!(
+                    "{} flag requires a regex pattern.",
+                    args[i]
+                )));
+            }
+        } else if args[i] == "--items-file" {
+            if let Some(path) = args.get(i + 1) {
+                config.items_file = Some(path.clone());
+                i += 1; // Skip the next argument as it's the file path
+            } else {
+                return Err(ParseError::InvalidArgument(
+                    "--items-file flag requires a file path.".to_string(),
+                ));
+            }
+        } else if args[i] == "--from" {
+            config.date_from = Some(parse_date_flag("--from", args.get(i + 1))?);
+            i += 1; // Skip the next argument as it's the date
+        } else if args[i] == "--to" {
+            config.date_to = Some(parse_date_flag("--to", args.get(i + 1))?);
+            i += 1; // Skip the next argument as it's the date
         }
         i += 1;
     }
+    // Inputs given on the command line replace the config file's list rather than adding to it.
+    if !cli_paths.is_empty() {
+        config.file_paths = cli_paths;
+    }
+    if !cli_author_filters.is_empty() {
+        config.author_filters = cli_author_filters;
+    }
+    if !cli_author_excludes.is_empty() {
+        config.author_excludes = cli_author_excludes;
+    }
+    if !cli_item_filters.is_empty() {
+        config.item_filters = cli_item_filters;
+    }
+    if !cli_item_excludes.is_empty() {
+        config.item_excludes = cli_item_excludes;
+    }
+
+    if serve_bind.is_some() && serve.is_none() {
+        return Err(ParseError::InvalidArgument(
+            "--serve-bind only applies together with --serve PORT.".to_string(),
+        ));
+    }
+
+    if dump_config {
+        print!("{}", Config::dump(&config)?);
+        return Ok(());
+    }
+    if let Some(iterations) = benchmark {
+        return run_benchmark(&config, iterations);
+    }
+    if dry_run {
+        if cli_quiet {
+            return Err(ParseError::InvalidArgument(
+                "--quiet cannot be used with --dry-run, which always reports its progress."
+                    .to_string(),
+            ));
+        }
+        // The progress log is the only output, so it is always shown.
+        config.is_verbose = true;
+        config.is_quiet = false;
+        return print_dry_run_summary(&analyze_trades(&config)?);
+    }
 
-    // Pass the dynamic file_path and is_verbose flag
-    let yaml_output = parser::run_trade_analysis(&file_path, is_verbose)?;
-    println!("{}", yaml_output);
+    let mut out: Box<dyn Write> = match &config.output_path {
+        Some(path) => Box::new(BufWriter::new(open_output_file(
+            path,
+            config.append_output,
+        )?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    if let Some(sql) = sqlite_query {
+        let Some(path) = &sqlite else {
+            return Err(ParseError::InvalidArgument(
+                "--sqlite-query needs --sqlite PATH to name the database.".to_string(),
+            ));
+        };
+        let result = query_sqlite(path, &sql)?;
+        writeln!(
+            out,
+            "{}",
+            render_query_result(&result, config.output_format)?
+        )?;
+    } else if let Some((a, b)) = merge {
+        let merged = merge_outputs(&load_analysis(&a)?, &load_analysis(&b)?, &config)?;
+        writeln!(out, "{}", render_output(&merged, &config)?)?;
+    } else if let Some((a, b)) = compare {
+        let report = compare_outputs(
+            &load_comparison_input(&a, &config)?,
+            &load_comparison_input(&b, &config)?,
+        );
+        writeln!(out, "{}", render_comparison(&report, config.output_format)?)?;
+    } else if watch || serve.is_some() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let handler_stop = Arc::clone(&stop);
+        ctrlc::set_handler(move || handler_stop.store(true, Ordering::SeqCst))
+            .map_err(|e| ParseError::Io(io::Error::other(e)))?;
+        match serve {
+            Some(port) => {
+                // Loopback unless asked otherwise, so the analysis stays on this machine.
+                let ip = serve_bind.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+                serve_trade_analysis(&config, SocketAddr::new(ip, port), &stop)?
+            }
+            None => watch_trade_analysis(&config, &mut out, &stop)?,
+        }
+    } else if let Some(path) = &sqlite {
+        // The database needs every item, so this run never streams.
+        let analysis = analyze_trades(&config)?;
+        let run_id = save_to_sqlite(&analysis, path)?;
+        if config.is_verbose {
+            eprintln!(
+                "Saved {} items to '{}' as run '{}'.",
+                analysis.items.len(),
+                path,
+                run_id
+            );
+        }
+        write_analysis_to(analysis, &config, &mut out)?;
+    } else {
+        run_trade_analysis_to(&config, &mut out)?;
+    }
+    out.flush()?;
     Ok(())
 }
@@ -0,0 +1,63 @@
+// src/nonblocking.rs
+
+//! An async entry point for applications already running on tokio, such as an
+//! HTTP handler, where blocking file reads would stall the executor. Enabled by
+//! the `async` feature.
+
+use tokio::fs::File;
+use tokio::io::{self, AsyncReadExt, BufReader};
+use tokio::task::{JoinError, spawn_blocking};
+
+use crate::config::RunConfig;
+use crate::error::ParseError;
+use crate::parser::{AnalysisOutput, analyze_loaded_inputs, resolve_input_paths};
+
+/// [`analyze_trades`](crate::analyze_trades) without blocking the runtime: the
+/// inputs are read with tokio's file I/O, and the directory scan and the CSV pass,
+/// which are CPU-bound, run on the blocking thread pool.
+///
+/// `--mmap` has no effect here, as every input is read into memory first.
+pub async fn run_trade_analysis_async(config: RunConfig) -> Result<AnalysisOutput, ParseError> {
+    let scan_config = config.clone();
+    let input_paths = spawn_blocking(move || resolve_input_paths(&scan_config))
+        .await
+        .map_err(task_error)??;
+
+    let mut inputs = Vec::with_capacity(input_paths.len());
+    for file_path in input_paths {
+        let bytes = read_input(&file_path, &config).await?;
+        inputs.push((file_path, bytes));
+    }
+
+    spawn_blocking(move || analyze_loaded_inputs(&config, inputs))
+        .await
+        .map_err(task_error)?
+}
+
+/// The whole of one input, `-` or an empty path being stdin.
+async fn read_input(file_path: &str, config: &RunConfig) -> Result<Vec<u8>, ParseError> {
+    let mut bytes = Vec::new();
+    if file_path.is_empty() || file_path == "-" {
+        if config.is_verbose {
+            eprintln!("Reading CSV data from stdin...");
+        }
+        BufReader::new(io::stdin()).read_to_end(&mut bytes).await?;
+        return Ok(bytes);
+    }
+    if config.is_verbose {
+        eprintln!("Attempting to open CSV file: '{}'", file_path);
+    }
+    let file = File::open(file_path).await.map_err(|e| {
+        if !config.is_quiet {
+            eprintln!("ERROR: Could not open file '{}': {}", file_path, e);
+        }
+        ParseError::FileNotFound(e)
+    })?;
+    BufReader::new(file).read_to_end(&mut bytes).await?;
+    Ok(bytes)
+}
+
+/// A blocking task that panicked or was cancelled with the runtime.
+fn task_error(e: JoinError) -> ParseError {
+    ParseError::Io(std::io::Error::other(e))
+}
@@ -0,0 +1,58 @@
+// src/output/csv.rs
+
+use std::io;
+
+use crate::error::ParseError;
+use crate::parser::AnalysisOutput;
+
+const HEADER: [&str; 14] = [
+    "item",
+    "median_price",
+    "min_price",
+    "max_price",
+    "supply_posts",
+    "demand_posts",
+    "chance_to_buy",
+    "chance_to_sell",
+    "rough_selling_frequency",
+    "p25_price",
+    "p75_price",
+    "iqr",
+    "swap_posts",
+    "mean_price",
+];
+
+/// Renders one CSV row per item. Metadata is deliberately left out so the
+/// result stays a plain table that spreadsheets can import directly.
+pub(super) fn render(analysis: &AnalysisOutput) -> Result<String, ParseError> {
+    let mut writer = ::csv::Writer::from_writer(Vec::new());
+    writer.write_record(HEADER)?;
+    for item in &analysis.items {
+        writer.write_record([
+            item.item.clone(),
+            format_price(item.estimated_price.median),
+            format_price(item.estimated_price.min),
+            format_price(item.estimated_price.max),
+            item.supply_demand.supply_posts.to_string(),
+            item.supply_demand.demand_posts.to_string(),
+            item.estimated_trade_chances.chance_to_buy.clone(),
+            item.estimated_trade_chances.chance_to_sell.clone(),
+            item.rough_selling_frequency.clone(),
+            format_price(item.estimated_price.p25),
+            format_price(item.estimated_price.p75),
+            format_price(item.estimated_price.iqr),
+            item.supply_demand.swap_posts.to_string(),
+            format_price(item.estimated_price.mean),
+        ])?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| ParseError::Io(e.into_error()))?;
+    // Every field is built from Rust strings, so the buffer is always valid UTF-8.
+    String::from_utf8(bytes)
+        .map_err(|e| ParseError::Io(io::Error::new(io::ErrorKind::InvalidData, e)))
+}
+
+fn format_price(price: Option<f64>) -> String {
+    price.map_or(String::new(), |p| format!("{:.2}", p))
+}
@@ -0,0 +1,42 @@
+// src/output/history.rs
+
+//! `--history-csv`: each item's raw price history as its own CSV, for
+//! spreadsheets and charting tools, keeping the main output compact.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use super::split::unique_file_name;
+use crate::error::ParseError;
+use crate::parser::AnalysisOutput;
+
+const HEADER: [&str; 4] = ["date", "price", "direction", "author"];
+
+/// Writes `{item}_history.csv` for every item, oldest price first, creating `dir`
+/// first if needed.
+pub(super) fn write(analysis: &AnalysisOutput, dir: &str) -> Result<usize, ParseError> {
+    let dir_error = |e| ParseError::OutputDirError(dir.to_string(), e);
+    let root = Path::new(dir);
+    fs::create_dir_all(root).map_err(dir_error)?;
+
+    let mut used_names = HashSet::new();
+    for item in &analysis.items {
+        let file = unique_file_name(&item.item, "_history.csv", &mut used_names);
+        let mut writer = ::csv::Writer::from_writer(Vec::new());
+        writer.write_record(HEADER)?;
+        for point in &item.price_history {
+            writer.write_record([
+                point.date.as_str(),
+                &point.price.to_string(),
+                &point.direction,
+                &point.author,
+            ])?;
+        }
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| ParseError::Io(e.into_error()))?;
+        fs::write(root.join(file), bytes).map_err(dir_error)?;
+    }
+    Ok(analysis.items.len())
+}
@@ -0,0 +1,160 @@
+// src/output/html.rs
+
+use crate::parser::{AnalysisOutput, ItemAnalysis};
+
+use super::metadata_lines;
+
+const STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; margin: 2rem; color: #222; background: #fafafa; }
+header { background: #fff; border: 1px solid #ddd; border-radius: 6px; padding: 1rem 1.5rem; margin-bottom: 1.5rem; }
+header h1 { margin-top: 0; font-size: 1.4rem; }
+header ul { margin: 0; padding-left: 1.2rem; font-size: 0.9rem; color: #555; }
+table { border-collapse: collapse; width: 100%; background: #fff; font-size: 0.9rem; }
+th, td { border: 1px solid #ddd; padding: 0.4rem 0.6rem; text-align: right; }
+th { background: #333; color: #fff; cursor: pointer; user-select: none; }
+th:first-child, td:first-child { text-align: left; }
+tr.supply { background: #e6f4ea; }
+tr.demand { background: #fce8e6; }
+tr.balanced { background: #f1f3f4; }
+.range { display: inline-block; width: 100px; height: 8px; background: #ddd; border-radius: 4px; vertical-align: middle; margin-left: 0.5rem; }
+.range span { display: block; height: 100%; background: #1a73e8; border-radius: 4px; }
+"#;
+
+const SCRIPT: &str = r#"
+document.querySelectorAll("th").forEach(function (th, column) {
+  th.addEventListener("click", function () {
+    var tbody = th.closest("table").querySelector("tbody");
+    var ascending = th.dataset.order !== "asc";
+    th.dataset.order = ascending ? "asc" : "desc";
+    var rows = Array.prototype.slice.call(tbody.rows);
+    rows.sort(function (a, b) {
+      var x = a.cells[column].dataset.sort, y = b.cells[column].dataset.sort;
+      var nx = parseFloat(x), ny = parseFloat(y);
+      var cmp = isNaN(nx) || isNaN(ny) ? x.localeCompare(y) : nx - ny;
+      return ascending ? cmp : -cmp;
+    });
+    rows.forEach(function (row) { tbody.appendChild(row); });
+  });
+});
+"#;
+
+/// Renders a standalone HTML report: metadata in a `<header>`, then a table whose
+/// columns can be sorted by clicking their headings. Rows are tinted green when
+/// supply outweighs demand, red when demand outweighs supply and grey otherwise.
+pub(super) fn render(analysis: &AnalysisOutput) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Go Skate Trade Analysis</title>\n",
+    );
+    out.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+
+    out.push_str("<header>\n<h1>Trade Analysis Metadata</h1>\n<ul>\n");
+    for line in metadata_lines(analysis) {
+        out.push_str(&format!("<li>{}</li>\n", escape_html(&line)));
+    }
+    out.push_str("</ul>\n</header>\n");
+
+    // Every range bar is drawn against the most expensive observation in the report.
+    let scale_max = analysis
+        .items
+        .iter()
+        .filter_map(|item| item.estimated_price.max)
+        .fold(0.0, f64::max);
+
+    out.push_str(
+        "<table>\n<thead>\n<tr><th>Item</th><th>Median Price</th><th>Min – Max</th>\
+         <th>P25</th><th>P75</th><th>Supply Posts</th><th>Demand Posts</th>\
+         <th>Chance to Buy</th><th>Chance to Sell</th><th>Frequency</th></tr>\n</thead>\n<tbody>\n",
+    );
+    for item in &analysis.items {
+        out.push_str(&render_row(item, scale_max));
+    }
+    out.push_str("</tbody>\n</table>\n");
+    out.push_str(&format!("<script>{}</script>\n</body>\n</html>\n", SCRIPT));
+    out
+}
+
+fn render_row(item: &ItemAnalysis, scale_max: f64) -> String {
+    let supply_demand = &item.supply_demand;
+    let class = match supply_demand.supply_posts.cmp(&supply_demand.demand_posts) {
+        std::cmp::Ordering::Greater => "supply",
+        std::cmp::Ordering::Less => "demand",
+        std::cmp::Ordering::Equal => "balanced",
+    };
+    let price = &item.estimated_price;
+    let chances = &item.estimated_trade_chances;
+    format!(
+        "<tr class=\"{}\">{}{}{}{}{}{}{}{}{}{}</tr>\n",
+        class,
+        text_cell(&item.item),
+        price_cell(price.median),
+        range_cell(price.min, price.max, scale_max),
+        price_cell(price.p25),
+        price_cell(price.p75),
+        number_cell(
+            supply_demand.supply_posts as f64,
+            &supply_demand.supply_posts.to_string()
+        ),
+        number_cell(
+            supply_demand.demand_posts as f64,
+            &supply_demand.demand_posts.to_string()
+        ),
+        percent_cell(&chances.chance_to_buy),
+        percent_cell(&chances.chance_to_sell),
+        text_cell(&item.rough_selling_frequency),
+    )
+}
+
+fn text_cell(text: &str) -> String {
+    let escaped = escape_html(text);
+    format!("<td data-sort=\"{}\">{}</td>", escaped, escaped)
+}
+
+/// `data-sort` holds the raw value so the script sorts numerically, not by label.
+fn number_cell(sort_value: f64, label: &str) -> String {
+    format!(
+        "<td data-sort=\"{}\">{}</td>",
+        sort_value,
+        escape_html(label)
+    )
+}
+
+fn price_cell(price: Option<f64>) -> String {
+    match price {
+        Some(p) => number_cell(p, &format!("{:.2}", p)),
+        None => "<td data-sort=\"-1\">N/A</td>".to_string(),
+    }
+}
+
+/// Chances are pre-formatted as `"12.34%"`; sort on the numeric part.
+fn percent_cell(chance: &str) -> String {
+    let value = chance.trim_end_matches('%').parse().unwrap_or(0.0);
+    number_cell(value, chance)
+}
+
+/// The min/max labels followed by a bar spanning the item's range on a shared scale.
+fn range_cell(min: Option<f64>, max: Option<f64>, scale_max: f64) -> String {
+    let (Some(min), Some(max)) = (min, max) else {
+        return "<td data-sort=\"-1\">N/A</td>".to_string();
+    };
+    // A log scale keeps cheap items readable next to the occasional huge listing.
+    let position = |price: f64| (1.0 + price.max(0.0)).log10() / (1.0 + scale_max).log10() * 100.0;
+    let (offset, width) = if scale_max > 0.0 {
+        // Keep single-price items visible as a thin sliver.
+        let offset = position(min).min(99.0);
+        (offset, (position(max) - offset).max(1.0))
+    } else {
+        (0.0, 0.0)
+    };
+    format!(
+        "<td data-sort=\"{}\">{:.2} – {:.2}<span class=\"range\"><span style=\"margin-left: {:.1}%; width: {:.1}%\"></span></span></td>",
+        min, min, max, offset, width
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,78 @@
+// src/output/jsonl.rs
+
+//! Newline-delimited JSON: one object per line, each tagged with a `"type"` of
+//! `"metadata"`, `"author"` or `"item"` so streaming consumers can dispatch on it.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::error::ParseError;
+use crate::parser::{AnalysisMetadata, AnalysisOutput, ItemAnalysis, serialize_weekly_pattern};
+
+#[derive(Serialize)]
+struct MetadataLine<'a> {
+    r#type: &'static str,
+    #[serde(flatten)]
+    metadata: &'a AnalysisMetadata,
+    total_parsing_time_ms: u128,
+    overall_trade_data_span_days: f64,
+    overall_trade_data_span_weeks: f64,
+    overall_trade_data_span_months: f64,
+    global_volume: f64,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    currency: &'a str,
+    #[serde(serialize_with = "serialize_weekly_pattern")]
+    weekly_pattern: &'a HashMap<String, u32>,
+    weekend_to_weekday_ratio: f64,
+    hourly_activity: [u32; 24],
+    peak_hour_utc: Option<u8>,
+}
+
+#[derive(Serialize)]
+struct Tagged<'a, T> {
+    r#type: &'static str,
+    #[serde(flatten)]
+    record: &'a T,
+}
+
+/// Renders a finished analysis: the header lines followed by one line per item.
+pub(super) fn render(analysis: &AnalysisOutput) -> Result<String, ParseError> {
+    let mut lines = vec![header_lines(analysis)?];
+    for item in &analysis.items {
+        lines.push(item_line(item)?);
+    }
+    Ok(lines.join("\n"))
+}
+
+/// The metadata line, then one line per author. Ignores `analysis.items`, so it
+/// can be written before any item has been computed.
+pub(crate) fn header_lines(analysis: &AnalysisOutput) -> Result<String, ParseError> {
+    let mut lines = vec![serde_json::to_string(&MetadataLine {
+        r#type: "metadata",
+        metadata: &analysis.metadata,
+        total_parsing_time_ms: analysis.total_parsing_time_ms,
+        overall_trade_data_span_days: analysis.overall_trade_data_span_days,
+        overall_trade_data_span_weeks: analysis.overall_trade_data_span_weeks,
+        overall_trade_data_span_months: analysis.overall_trade_data_span_months,
+        global_volume: analysis.global_volume,
+        currency: &analysis.currency,
+        weekly_pattern: &analysis.weekly_pattern,
+        weekend_to_weekday_ratio: analysis.weekend_to_weekday_ratio,
+        hourly_activity: analysis.hourly_activity,
+        peak_hour_utc: analysis.peak_hour_utc,
+    })?];
+    for author in &analysis.authors {
+        lines.push(serde_json::to_string(&Tagged {
+            r#type: "author",
+            record: author,
+        })?);
+    }
+    Ok(lines.join("\n"))
+}
+
+pub(crate) fn item_line(item: &ItemAnalysis) -> Result<String, ParseError> {
+    Ok(serde_json::to_string(&Tagged {
+        r#type: "item",
+        record: item,
+    })?)
+}
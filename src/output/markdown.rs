@@ -0,0 +1,172 @@
+// src/output/markdown.rs
+
+use crate::compare::ComparisonReport;
+use crate::parser::{AnalysisOutput, ItemAnalysis};
+use crate::summary::AnalysisSummary;
+
+use super::{format_money, metadata_lines};
+
+/// Renders the analysis as a Markdown table, with the metadata as a blockquote above it.
+pub(super) fn render(analysis: &AnalysisOutput) -> String {
+    let mut out = String::from("> **Trade Analysis Metadata**\n>\n");
+    for line in metadata_lines(analysis) {
+        out.push_str(&format!("> {}  \n", line));
+    }
+    out.push('\n');
+    out.push_str(&item_table(&analysis.items, &analysis.currency));
+    out
+}
+
+/// Renders a summary as a short Discord-pasteable report: the metadata, the
+/// standout items and a table of the busiest ones.
+pub(super) fn render_summary(analysis: &AnalysisOutput, summary: &AnalysisSummary) -> String {
+    let mut out = String::from("> **Trade Analysis Metadata**\n>\n");
+    for line in metadata_lines(analysis) {
+        out.push_str(&format!("> {}  \n", line));
+    }
+    out.push_str("\n**Market Snapshot**\n\n");
+
+    out.push_str(&highlight(
+        "Most expensive",
+        &summary.most_expensive,
+        |item| {
+            format!(
+                "median {}",
+                format_price(item.estimated_price.median, &analysis.currency)
+            )
+        },
+    ));
+    out.push_str(&highlight("Most traded", &summary.most_traded, |item| {
+        format!(
+            "{} posts",
+            item.supply_demand.supply_posts + item.supply_demand.demand_posts
+        )
+    }));
+    out.push_str(&highlight(
+        "Best chance to sell",
+        &summary.highest_sell_chance,
+        |item| item.estimated_trade_chances.chance_to_sell.clone(),
+    ));
+    out.push_str(&highlight(
+        "Best chance to buy",
+        &summary.highest_buy_chance,
+        |item| item.estimated_trade_chances.chance_to_buy.clone(),
+    ));
+    out.push_str(&format!(
+        "\n**Top {} of {} items by volume**\n\n",
+        summary.top_items.len(),
+        summary.items_analyzed
+    ));
+    out.push_str(&item_table(&summary.top_items, &analysis.currency));
+    out
+}
+
+/// One bullet naming a standout item, with `detail` explaining why it stands out.
+fn highlight(
+    label: &str,
+    item: &Option<ItemAnalysis>,
+    detail: impl Fn(&ItemAnalysis) -> String,
+) -> String {
+    match item {
+        Some(item) => format!(
+            "- **{}:** {} ({})\n",
+            label,
+            escape_cell(&item.item),
+            detail(item)
+        ),
+        None => format!("- **{}:** N/A\n", label),
+    }
+}
+
+fn item_table(items: &[ItemAnalysis], currency: &str) -> String {
+    let mut out = String::from(
+        "| Item | Median Price | Min | Max | P25–P75 | Supply Posts | Demand Posts | Trade Chance (Buy/Sell) | Frequency |\n",
+    );
+    out.push_str("| --- | ---: | ---: | ---: | ---: | ---: | ---: | :---: | --- |\n");
+    // Items are already in the output's sort order, so rows keep the same ordering.
+    for item in items {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} – {} | {} | {} | {} / {} | {} |\n",
+            escape_cell(&item.item),
+            format_price(item.estimated_price.median, currency),
+            format_price(item.estimated_price.min, currency),
+            format_price(item.estimated_price.max, currency),
+            format_price(item.estimated_price.p25, currency),
+            format_price(item.estimated_price.p75, currency),
+            item.supply_demand.supply_posts,
+            item.supply_demand.demand_posts,
+            item.estimated_trade_chances.chance_to_buy,
+            item.estimated_trade_chances.chance_to_sell,
+            escape_cell(&item.rough_selling_frequency),
+        ));
+    }
+    out
+}
+
+/// Renders a comparison as lists of new and removed items followed by a table of
+/// per-item changes. Significant price moves are shown in bold.
+pub(super) fn render_comparison(report: &ComparisonReport) -> String {
+    let mut out = String::from("## Market Changes\n\n");
+    out.push_str(&format!(
+        "**Market index:** {} → {} ({})  \n",
+        format_price(report.market_index_before, ""),
+        format_price(report.market_index_after, ""),
+        report
+            .market_index_change_percentage
+            .map_or("N/A".to_string(), |p| format!("{:+.1}%", p))
+    ));
+    out.push_str(&format!(
+        "**New items:** {}  \n",
+        item_list(&report.new_items)
+    ));
+    out.push_str(&format!(
+        "**Removed items:** {}\n\n",
+        item_list(&report.removed_items)
+    ));
+
+    out.push_str(
+        "| Item | Median Before | Median After | Change | Supply Posts | Demand Posts |\n",
+    );
+    out.push_str("| --- | ---: | ---: | ---: | ---: | ---: |\n");
+    for delta in &report.item_changes {
+        let change = delta
+            .median_price_change_percentage
+            .map_or("N/A".to_string(), |p| format!("{:+.1}%", p));
+        let change = if delta.significant_price_change {
+            format!("**{}**", change)
+        } else {
+            change
+        };
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} → {} ({:+}) | {} → {} ({:+}) |\n",
+            escape_cell(&delta.item),
+            format_price(delta.median_price_before, ""),
+            format_price(delta.median_price_after, ""),
+            change,
+            delta.supply_posts_before,
+            delta.supply_posts_after,
+            delta.supply_posts_change,
+            delta.demand_posts_before,
+            delta.demand_posts_after,
+            delta.demand_posts_change,
+        ));
+    }
+    out
+}
+
+fn item_list(items: &[String]) -> String {
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        escape_cell(&items.join(", "))
+    }
+}
+
+fn format_price(price: Option<f64>, currency: &str) -> String {
+    price.map_or("N/A".to_string(), |p| format_money(p, currency))
+}
+
+/// Pipes would otherwise be read as column separators.
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
@@ -0,0 +1,347 @@
+// src/output/mod.rs
+
+//! Serializers that turn a finished [`AnalysisOutput`] into text.
+
+mod csv;
+mod history;
+mod html;
+pub(crate) mod jsonl;
+mod markdown;
+mod split;
+
+use serde::Serialize;
+use std::fs;
+
+use crate::compare::ComparisonReport;
+use crate::config::{OutputFormat, RunConfig};
+use crate::error::ParseError;
+use crate::parser::{AnalysisMetadata, AnalysisOutput};
+use crate::sqlite::QueryResult;
+use crate::summary::{AnalysisSummary, summarize};
+
+/// JSON has no comment syntax, so the metadata block is embedded as a
+/// top-level `"metadata"` key alongside the regular output fields. TOML output
+/// uses the same shape, giving a `[metadata]` table.
+#[derive(Debug, Serialize)]
+struct JsonOutput<'a> {
+    metadata: &'a AnalysisMetadata,
+    #[serde(flatten)]
+    analysis: &'a AnalysisOutput,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonSummary<'a> {
+    metadata: &'a AnalysisMetadata,
+    #[serde(flatten)]
+    summary: &'a AnalysisSummary,
+}
+
+/// Serializes a finished analysis into the requested output format.
+/// With `config.summary` set, only its [`summarize`] snapshot is written.
+pub fn render_output(analysis: &AnalysisOutput, config: &RunConfig) -> Result<String, ParseError> {
+    if config.summary {
+        return render_summary(analysis, config);
+    }
+    let is_verbose = config.is_verbose;
+    let metadata = &analysis.metadata;
+    let output = match config.output_format {
+        OutputFormat::Yaml => {
+            if is_verbose {
+                eprintln!("Serializing results to YAML format...");
+            }
+            let metadata_comments = yaml_metadata_comments(analysis);
+            let yaml_items_output = serde_yaml::to_string(analysis)?;
+            format!("{}\n{}", metadata_comments, yaml_items_output)
+        }
+        OutputFormat::Json | OutputFormat::JsonPretty => {
+            if is_verbose {
+                eprintln!("Serializing results to JSON format...");
+            }
+            let json_output = JsonOutput { metadata, analysis };
+            if config.output_format == OutputFormat::JsonPretty {
+                serde_json::to_string_pretty(&json_output)?
+            } else {
+                serde_json::to_string(&json_output)?
+            }
+        }
+        OutputFormat::Jsonl => {
+            if is_verbose {
+                eprintln!("Serializing results to JSON Lines format...");
+            }
+            jsonl::render(analysis)?
+        }
+        OutputFormat::Markdown => {
+            if is_verbose {
+                eprintln!("Rendering results as a Markdown table...");
+            }
+            markdown::render(analysis)
+        }
+        OutputFormat::Html => {
+            if is_verbose {
+                eprintln!("Rendering results as an HTML report...");
+            }
+            html::render(analysis)
+        }
+        OutputFormat::Toml => {
+            if is_verbose {
+                eprintln!("Serializing results to TOML format...");
+            }
+            toml::to_string(&JsonOutput { metadata, analysis })?
+        }
+        OutputFormat::Csv => {
+            if is_verbose {
+                eprintln!("Serializing results to CSV format...");
+            }
+            // Keep stdout pure CSV: metadata goes to its own file, or to stderr.
+            let metadata_text = metadata_lines(analysis).join("\n");
+            match &config.meta_output {
+                Some(path) => fs::write(path, metadata_text + "\n")?,
+                None if !config.is_quiet => eprintln!("{}", metadata_text),
+                None => {}
+            }
+            csv::render(analysis)?
+        }
+    };
+    if is_verbose {
+        eprintln!("Serialization complete.");
+    }
+    Ok(output)
+}
+
+fn render_summary(analysis: &AnalysisOutput, config: &RunConfig) -> Result<String, ParseError> {
+    let summary = summarize(analysis);
+    let metadata = &analysis.metadata;
+    match config.output_format {
+        OutputFormat::Yaml => Ok(format!(
+            "{}\n{}",
+            yaml_metadata_comments(analysis),
+            serde_yaml::to_string(&summary)?
+        )),
+        OutputFormat::Json => Ok(serde_json::to_string(&JsonSummary {
+            metadata,
+            summary: &summary,
+        })?),
+        OutputFormat::JsonPretty => Ok(serde_json::to_string_pretty(&JsonSummary {
+            metadata,
+            summary: &summary,
+        })?),
+        OutputFormat::Toml => Ok(toml::to_string(&JsonSummary {
+            metadata,
+            summary: &summary,
+        })?),
+        OutputFormat::Markdown => Ok(markdown::render_summary(analysis, &summary)),
+        other => Err(ParseError::InvalidArgument(format!(
+            "--summary cannot write '{}' output; use yaml, json, json-pretty, toml or markdown.",
+            other
+        ))),
+    }
+}
+
+fn yaml_metadata_comments(analysis: &AnalysisOutput) -> String {
+    let mut comments = "# Trade Analysis Metadata\n# ------------------------\n".to_string();
+    for line in metadata_lines(analysis) {
+        comments.push_str(&format!("# {}\n", line));
+    }
+    comments
+}
+
+/// Writes one `{item}.yaml` per item into `dir`, each with the metadata block, plus a
+/// `manifest.yaml` listing every file with its item name and median price. `dir` is
+/// created if it doesn't exist. Returns the number of item files written.
+pub fn write_split_output(analysis: &AnalysisOutput, dir: &str) -> Result<usize, ParseError> {
+    split::write(analysis, dir)
+}
+
+/// Serializes a [`ComparisonReport`]; only YAML, JSON and Markdown are supported.
+pub fn render_comparison(
+    report: &ComparisonReport,
+    format: OutputFormat,
+) -> Result<String, ParseError> {
+    match format {
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(report)?),
+        OutputFormat::Json => Ok(serde_json::to_string(report)?),
+        OutputFormat::JsonPretty => Ok(serde_json::to_string_pretty(report)?),
+        OutputFormat::Markdown => Ok(markdown::render_comparison(report)),
+        other => Err(ParseError::InvalidArgument(format!(
+            "--compare cannot write '{}' output; use yaml, json, json-pretty or markdown.",
+            other
+        ))),
+    }
+}
+
+/// Writes one `{item}_history.csv` per item into `dir`, with the columns
+/// `date,price,direction,author` in date order. `dir` is created if it doesn't
+/// exist. The items need their `price_history`, so analyze with `include_history`
+/// or `history_csv` set. Returns the number of files written.
+pub fn write_history_csv(analysis: &AnalysisOutput, dir: &str) -> Result<usize, ParseError> {
+    history::write(analysis, dir)
+}
+
+/// Serializes `--sqlite-query` rows. CSV gives a header of column names and one
+/// line per row; the structured formats keep `columns` and `rows` apart.
+pub fn render_query_result(
+    result: &QueryResult,
+    format: OutputFormat,
+) -> Result<String, ParseError> {
+    match format {
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(result)?),
+        OutputFormat::Json => Ok(serde_json::to_string(result)?),
+        OutputFormat::JsonPretty => Ok(serde_json::to_string_pretty(result)?),
+        OutputFormat::Csv => {
+            let mut writer = ::csv::Writer::from_writer(Vec::new());
+            writer.write_record(&result.columns)?;
+            for row in &result.rows {
+                writer.write_record(row.iter().map(|value| match value {
+                    serde_json::Value::Null => String::new(),
+                    serde_json::Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                }))?;
+            }
+            let bytes = writer
+                .into_inner()
+                .map_err(|e| ParseError::Io(e.into_error()))?;
+            let text = String::from_utf8_lossy(&bytes);
+            Ok(text.strip_suffix('\n').unwrap_or(&text).to_string())
+        }
+        other => Err(ParseError::InvalidArgument(format!(
+            "--sqlite-query cannot write '{}' output; use yaml, json, json-pretty or csv.",
+            other
+        ))),
+    }
+}
+
+/// The symbol a `--currency` value stands for: the codes `USD`, `EUR`, `GBP` and
+/// `JPY` map to theirs, and anything else is used as given.
+fn currency_symbol(currency: &str) -> &str {
+    match currency.to_uppercase().as_str() {
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" => "¥",
+        _ => currency,
+    }
+}
+
+/// `price` to two decimals. With a currency it also gets the symbol and
+/// thousands separators, as in `$1,234.56`.
+fn format_money(price: f64, currency: &str) -> String {
+    // Adding zero turns the -0.0 of an empty sum into 0.0.
+    let plain = format!("{:.2}", price + 0.0);
+    if currency.is_empty() {
+        return plain;
+    }
+    let (sign, digits) = match plain.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", plain.as_str()),
+    };
+    let (whole, cents) = digits.split_once('.').unwrap_or((digits, "00"));
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}{}{}.{}", sign, currency_symbol(currency), grouped, cents)
+}
+
+/// The human-readable metadata summary shared by the text-based formats.
+fn metadata_lines(analysis: &AnalysisOutput) -> Vec<String> {
+    let metadata = &analysis.metadata;
+    vec![
+        format!(
+            "Earliest message (UTC Epoch): {}",
+            metadata
+                .earliest_message_utc_epoch
+                .map_or("N/A".to_string(), |e| e.to_string())
+        ),
+        format!(
+            "Latest message (UTC Epoch): {}",
+            metadata
+                .latest_message_utc_epoch
+                .map_or("N/A".to_string(), |e| e.to_string())
+        ),
+        format!(
+            "Parser run time (UTC Epoch): {}",
+            metadata.parser_run_utc_epoch
+        ),
+        format!("CSV data time period: {}", metadata.csv_data_time_period),
+        format!(
+            "Total parsing and processing time: {} ms",
+            analysis.total_parsing_time_ms
+        ),
+        format!(
+            "Overall trade data span: {:.2} days ({:.2} weeks, {:.2} months)",
+            analysis.overall_trade_data_span_days,
+            analysis.overall_trade_data_span_weeks,
+            analysis.overall_trade_data_span_months
+        ),
+        format!(
+            "Global volume (sum of listed prices): {}",
+            format_money(analysis.global_volume, &analysis.currency)
+        ),
+        format!(
+            "Date filter window: {} to {}",
+            metadata.date_from.as_deref().unwrap_or("(open)"),
+            metadata.date_to.as_deref().unwrap_or("(open)")
+        ),
+        format!(
+            "Records outside date window: {}",
+            metadata.out_of_range_records_count
+        ),
+        format!(
+            "Input files: {}",
+            metadata
+                .input_files
+                .iter()
+                .map(|f| format!("{} ({} records)", f.path, f.records))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        format!("Records skipped: {}", metadata.skipped_records_count),
+        format!("Trade observations: {}", metadata.observations_count),
+        format!("Multi-item posts: {}", metadata.multi_item_posts_count),
+        format!(
+            "Prices outside the allowed range: {}",
+            metadata.price_filtered_count
+        ),
+        format!(
+            "Records skipped by reason: {}",
+            if metadata.skipped_by_reason.is_empty() {
+                "none".to_string()
+            } else {
+                metadata
+                    .skipped_by_reason
+                    .iter()
+                    .map(|(reason, count)| format!("{} {}", reason, count))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        ),
+        format!(
+            "Duplicate records dropped: {}",
+            metadata.duplicate_records_count
+        ),
+        format!("Spam reposts filtered: {}", metadata.spam_filtered_count),
+        format!(
+            "Records filtered by author: {}",
+            metadata.author_filtered_count
+        ),
+        format!("Items removed by item filter: {}", metadata.items_filtered),
+        format!(
+            "Items suppressed (too few posts): {}",
+            metadata.items_suppressed_low_data
+        ),
+        format!(
+            "Items below minimum confidence: {}",
+            metadata.items_below_min_confidence
+        ),
+        format!("Items omitted (--top): {}", metadata.items_omitted),
+        format!(
+            "Global market saturation (supply/demand): {}",
+            metadata
+                .global_market_saturation
+                .map_or("N/A".to_string(), |s| format!("{:.2}", s))
+        ),
+    ]
+}
@@ -0,0 +1,73 @@
+// src/output/split.rs
+
+//! `--split-output`: one YAML file per item plus a manifest, for grepping,
+//! diffing or version-controlling individual item histories.
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::error::ParseError;
+use crate::parser::AnalysisOutput;
+
+/// One line of `manifest.yaml`.
+#[derive(Debug, Serialize)]
+struct ManifestEntry<'a> {
+    item: &'a str,
+    file: String,
+    median_price: Option<f64>,
+}
+
+/// Writes `{item}.yaml` for every item, each headed by the metadata block, and a
+/// `manifest.yaml` listing them, creating `dir` first if needed.
+pub(super) fn write(analysis: &AnalysisOutput, dir: &str) -> Result<usize, ParseError> {
+    let dir_error = |e| ParseError::OutputDirError(dir.to_string(), e);
+    let root = Path::new(dir);
+    fs::create_dir_all(root).map_err(dir_error)?;
+
+    let metadata_comments = super::yaml_metadata_comments(analysis);
+    let mut used_names = HashSet::new();
+    let mut manifest = Vec::with_capacity(analysis.items.len());
+    for item in &analysis.items {
+        let file = unique_file_name(&item.item, ".yaml", &mut used_names);
+        let body = format!("{}\n{}", metadata_comments, serde_yaml::to_string(item)?);
+        fs::write(root.join(&file), body).map_err(dir_error)?;
+        manifest.push(ManifestEntry {
+            item: &item.item,
+            file,
+            median_price: item.estimated_price.median,
+        });
+    }
+    fs::write(
+        root.join("manifest.yaml"),
+        serde_yaml::to_string(&manifest)?,
+    )
+    .map_err(dir_error)?;
+    Ok(manifest.len())
+}
+
+/// `"Hot Spring"` with `ending` `.yaml` becomes `Hot_Spring.yaml`: spaces turn into
+/// underscores and anything but letters, digits, `_` and `-` is dropped. Names that
+/// collide after sanitizing get a numeric suffix, and `manifest` is reserved for the
+/// manifest.
+pub(super) fn unique_file_name(item: &str, ending: &str, used: &mut HashSet<String>) -> String {
+    let mut stem: String = item
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('_'),
+            c if c.is_alphanumeric() || c == '_' || c == '-' => Some(c),
+            _ => None,
+        })
+        .collect();
+    if stem.is_empty() {
+        stem = "item".to_string();
+    }
+    let mut candidate = stem.clone();
+    let mut suffix = 2;
+    while candidate.eq_ignore_ascii_case("manifest") || !used.insert(candidate.to_lowercase()) {
+        candidate = format!("{}_{}", stem, suffix);
+        suffix += 1;
+    }
+    format!("{}{}", candidate, ending)
+}
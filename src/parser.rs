@@ -1,434 +1,4024 @@
 // src/parser.rs
 
-use chrono::{DateTime, Duration, FixedOffset, Utc};
+use chrono::format::StrftimeItems;
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, Timelike, Utc, Weekday,
+};
 use csv::ReaderBuilder;
-use regex::{Regex, escape};
+use indicatif::{ProgressBar, ProgressStyle};
+use memmap2::Mmap;
+use rayon::ThreadPoolBuilder;
+use rayon::prelude::*;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, IsTerminal, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 use std::time::Instant;
 
-use crate::items;
+use crate::config::{NumberFormat, OutputFormat, RunConfig, SortKey};
+use crate::display::ascii_histogram;
+use crate::error::ParseError;
+use crate::items::{self, ItemAutomaton};
+use crate::output::{jsonl, render_output, write_history_csv, write_split_output};
+use crate::price_parser::PriceParser;
+use crate::stats::{median_of_unsorted, price_at_percentile};
+use crate::types::{AuthorId, ItemName, Price};
 
-#[derive(Debug, Deserialize)]
-struct TradeRecord {
+/// One row of a trade export, as read from the CSV or passed to
+/// [`TradeParser::feed_record`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct TradeRecord {
     #[serde(rename = "AuthorID")]
-    author_id: u64,
+    pub author_id: AuthorId,
     #[serde(rename = "Author")]
-    author: String,
+    pub author: String,
+    /// RFC 3339, or whatever `--date-format` and the fallback formats accept.
     #[serde(rename = "Date")]
-    date: String,
+    pub date: String,
     #[serde(rename = "Content")]
-    content: Option<String>,
+    pub content: Option<String>,
     #[serde(rename = "Attachments")]
-    attachments: Option<String>,
+    pub attachments: Option<String>,
     #[serde(rename = "Reactions")]
-    reactions: Option<String>,
+    pub reactions: Option<String>,
 }
 
-#[derive(Debug, Default)]
-struct ItemStats {
+/// The state of the item in a post, from the first condition keyword it mentions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Condition {
+    New,
+    NearMint,
+    Used,
+    #[default]
+    Unknown,
+}
+
+impl Condition {
+    fn from_keyword(keyword: &str) -> Condition {
+        match keyword.to_lowercase().as_str() {
+            "mint" => Condition::NearMint,
+            "used" | "worn" | "beater" | "scratched" | "cracked" => Condition::Used,
+            word if word.starts_with("near") => Condition::NearMint,
+            _ => Condition::New,
+        }
+    }
+
+    /// Reads back a name written by [`Condition::as_str`]; anything else is unknown.
+    fn from_name(name: &str) -> Self {
+        match name {
+            "new" => Condition::New,
+            "near_mint" => Condition::NearMint,
+            "used" => Condition::Used,
+            _ => Condition::Unknown,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Condition::New => "new",
+            Condition::NearMint => "near_mint",
+            Condition::Used => "used",
+            Condition::Unknown => "unknown",
+        }
+    }
+}
+
+/// Everything gathered for one item before its statistics are computed. Library
+/// callers can checkpoint it with [`snapshot`](ItemStats::snapshot) and
+/// [`restore`](ItemStats::restore) and combine partial results with
+/// [`merge`](ItemStats::merge).
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemStats {
+    /// Prices as listed, whatever the pack size.
     prices: Vec<f64>,
+    /// Each listed price divided by its pack size, aligned with `prices`.
+    unit_prices: Vec<f64>,
     supply_posts: u32,
     demand_posts: u32,
+    swap_posts: u32,
+    completed_posts: u32,
     trade_dates: Vec<DateTime<FixedOffset>>,
+    /// How each price was posted, aligned with `prices`.
+    post_kinds: Vec<PostKind>,
+    /// The condition each price was quoted for, aligned with `prices`.
+    conditions: Vec<Condition>,
+    /// The author who posted each price, aligned with `prices`.
+    authors: Vec<String>,
+    /// Posts per condition name, e.g. `"used" => 3`.
+    condition_breakdown: HashMap<String, u32>,
+    /// Prices from supply posts only (the asking side).
+    supply_prices: Vec<f64>,
+    /// Prices from demand posts only (the bidding side).
+    demand_prices: Vec<f64>,
+    range_derived_posts: u32,
+    flexible_posts: u32,
+    firm_posts: u32,
+    outliers_removed: u32,
 }
 
-#[derive(Debug, Serialize)]
-struct EstimatedPrice {
-    median: Option<f64>,
-    min: Option<f64>,
-    max: Option<f64>,
+/// How many records are read between progress bar redraws.
+const PROGRESS_TICK_INTERVAL: u32 = 100;
+
+/// How many records of the first input `--number-format auto` inspects.
+const NUMBER_FORMAT_SAMPLE_RECORDS: usize = 50;
+
+// The million suffix must end the token so "5mins" isn't read as five million.
+static PRICE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d[\d\.]*(?:[kK]|[mM]\b)?|\d[\d,\.]*)").unwrap());
+static RANGE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d[\d\.]*[kKmM]?)\s*[-–]\s*(\d[\d\.]*[kKmM]?)").unwrap());
+// European prices keep `.` for thousands and may end in a `,` decimal part.
+static EUROPEAN_PRICE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d[\d\.]*(?:,\d+)?(?:[kK]|[mM]\b)?)").unwrap());
+static EUROPEAN_RANGE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\d[\d\.]*(?:,\d+)?[kKmM]?)\s*[-–]\s*(\d[\d\.]*(?:,\d+)?[kKmM]?)").unwrap()
+});
+/// Digit runs containing a separator, which `--number-format auto` votes on.
+static SEPARATED_NUMBER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\d[\d,\.]*[,\.]\d+").unwrap());
+static SELL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(sell|selling|wts)\b").unwrap());
+static BUY_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(buy|buying|wtb)\b").unwrap());
+static SOLD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(sold|deal\s*done|completed)\b").unwrap());
+static SWAP_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(wtt|trade|trading|swap|swapping|exchange)\b").unwrap());
+static FLEXIBLE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(obo|or\s+best\s+offer|negotiable|flex)\b").unwrap());
+static FIRM_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(firm|fixed|no\s+offers)\b").unwrap());
+/// Multi-word conditions come first so "brand new" and "near mint" win over "new" and "mint".
+static CONDITION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(brand[\s-]new|near\s+mint|new|mint|used|worn|beater|scratched|cracked)\b")
+        .unwrap()
+});
+/// A pack size such as "10x", "100 x" or "x4"; bounded so "5 xbox" isn't read as one.
+static UNIT_QUANTITY_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(\d+)\s*x\b|\bx\s*(\d+)\b").unwrap());
+
+/// Tried in order once RFC 3339 and RFC 2822 have failed. None carries an offset,
+/// so matches are taken as UTC.
+const FALLBACK_DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%d-%b-%y %I:%M %p",
+    "%m/%d/%Y %I:%M %p",
+    "%d/%m/%Y %H:%M:%S",
+    "%d/%m/%Y %H:%M",
+];
+
+static ITEM_MATCHER: LazyLock<ItemAutomaton> = LazyLock::new(|| {
+    items::build_aho_corasick_matcher(None).expect("built-in item keywords are valid")
+});
+
+/// Patterns shared by every record classification. Cloning a compiled `Regex` is
+/// cheap, so building this per run costs nothing once the statics are initialized.
+struct Matchers {
+    /// Borrowed from `ITEM_MATCHER` unless `--items-file` is set.
+    items: Cow<'static, ItemAutomaton>,
+    price: Regex,
+    price_parser: PriceParser,
+    range: Regex,
+    sell: Regex,
+    buy: Regex,
+    sold: Regex,
+    swap: Regex,
+    flexible: Regex,
+    firm: Regex,
+    condition: Regex,
+    unit_quantity: Regex,
+    /// `--date-format`, tried before the built-in formats.
+    date_format: Option<String>,
+    authors: NameFilter,
 }
 
-#[derive(Debug, Serialize)]
-struct SupplyDemand {
-    supply_posts: u32,
-    demand_posts: u32,
+impl Matchers {
+    /// Uses the built-in patterns unless `config` overrides them. `input_paths` is
+    /// only read when the number format has to be detected.
+    fn new(config: &RunConfig, input_paths: &[String]) -> Result<Self, ParseError> {
+        if config.min_price > config.max_price {
+            return Err(ParseError::InvalidArgument(format!(
+                "--min-price {} is above --max-price {}.",
+                config.min_price, config.max_price
+            )));
+        }
+        // The built-in automaton is shared; an items file gets its own.
+        let items = match &config.items_file {
+            Some(path) => Cow::Owned(items::build_aho_corasick_matcher(Some(path))?),
+            None => Cow::Borrowed(&*ITEM_MATCHER),
+        };
+        let (default_price, range, price_parser) = match resolve_number_format(config, input_paths)
+        {
+            NumberFormat::European => (
+                &EUROPEAN_PRICE_REGEX,
+                EUROPEAN_RANGE_REGEX.clone(),
+                PriceParser::with_decimal_separator(','),
+            ),
+            _ => (&PRICE_REGEX, RANGE_REGEX.clone(), PriceParser::default()),
+        };
+        Ok(Matchers {
+            items,
+            price: override_regex("price_regex", &config.price_regex, default_price)?,
+            price_parser,
+            range,
+            sell: override_regex("sell_regex", &config.sell_regex, &SELL_REGEX)?,
+            buy: override_regex("buy_regex", &config.buy_regex, &BUY_REGEX)?,
+            sold: SOLD_REGEX.clone(),
+            swap: SWAP_REGEX.clone(),
+            flexible: FLEXIBLE_REGEX.clone(),
+            firm: FIRM_REGEX.clone(),
+            condition: CONDITION_REGEX.clone(),
+            unit_quantity: UNIT_QUANTITY_REGEX.clone(),
+            date_format: validate_date_format(&config.date_format)?,
+            authors: NameFilter::new(
+                ("--author-filter", &config.author_filters),
+                ("--author-exclude", &config.author_excludes),
+            )?,
+        })
+    }
 }
 
-#[derive(Debug, Serialize)]
-struct TradeChance {
-    chance_to_buy: String,
-    chance_to_sell: String,
+/// `config.number_format`, with `Auto` settled by [`detect_number_format`].
+fn resolve_number_format(config: &RunConfig, input_paths: &[String]) -> NumberFormat {
+    if config.number_format != NumberFormat::Auto {
+        return config.number_format;
+    }
+    let detected = detect_number_format(input_paths.first().map(String::as_str));
+    if config.is_verbose {
+        eprintln!("Reading prices in the {} number format.", detected);
+    }
+    detected
 }
 
-#[derive(Debug, Serialize)]
-struct ItemAnalysis {
-    item: String,
-    estimated_price: EstimatedPrice,
-    supply_demand: SupplyDemand,
-    estimated_trade_chances: TradeChance,
-    rough_selling_frequency: String,
+/// Guesses the number format from the first records of `path`. Each number with
+/// a separator votes by its last one: followed by exactly three digits it's a
+/// thousands separator (`1.200`), otherwise a decimal point (`2,5k`). European
+/// wins only with more votes; stdin can't be read twice, so it and unreadable
+/// files count as US.
+fn detect_number_format(path: Option<&str>) -> NumberFormat {
+    let Some(file) = path
+        .filter(|path| !path.is_empty() && *path != "-")
+        .and_then(|path| File::open(path).ok())
+    else {
+        return NumberFormat::Us;
+    };
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(BufReader::new(file));
+    let (mut european, mut us) = (0, 0);
+    for record in rdr
+        .deserialize::<TradeRecord>()
+        .filter_map(Result::ok)
+        .take(NUMBER_FORMAT_SAMPLE_RECORDS)
+    {
+        let content = record.content.as_deref().unwrap_or_default();
+        for token in SEPARATED_NUMBER_REGEX.find_iter(content) {
+            let token = token.as_str();
+            let Some(at) = token.rfind([',', '.']) else {
+                continue;
+            };
+            let is_thousands = token.len() - at - 1 == 3;
+            if (token.as_bytes()[at] == b',') != is_thousands {
+                european += 1;
+            } else {
+                us += 1;
+            }
+        }
+    }
+    if european > us {
+        NumberFormat::European
+    } else {
+        NumberFormat::Us
+    }
 }
 
-#[derive(Debug, Serialize)]
-struct AnalysisOutput {
-    total_parsing_time_ms: u128,
-    overall_trade_data_span_days: f64,
-    overall_trade_data_span_weeks: f64,
-    overall_trade_data_span_months: f64,
-    items: Vec<ItemAnalysis>,
+/// An include/exclude pair of regex lists, as given by `--author-filter` and
+/// `--author-exclude` or `--item-filter` and `--item-exclude`.
+struct NameFilter {
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
 }
 
-pub fn run_trade_analysis(
-    file_path: &str,
-    is_verbose: bool,
-) -> Result<String, Box<dyn std::error::Error>> {
-    if is_verbose {
-        println!("\n--- Starting Trade Analysis ---\n");
+impl NameFilter {
+    /// Compiles each `(flag, patterns)` list, naming the flag in any error.
+    fn new(include: (&str, &[String]), exclude: (&str, &[String])) -> Result<Self, ParseError> {
+        Ok(NameFilter {
+            include: compile_patterns(include.0, include.1)?,
+            exclude: compile_patterns(exclude.0, exclude.1)?,
+        })
     }
-    let start_time = Instant::now();
 
-    if is_verbose {
-        println!("Attempting to open CSV file: '{}'", file_path);
+    /// True when `name` matches any include pattern (or none were given) and no
+    /// exclude pattern.
+    fn allows(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|re| re.is_match(name)))
+            && !self.exclude.iter().any(|re| re.is_match(name))
     }
-    let file = File::open(file_path);
-    let file = match file {
-        Ok(f) => {
-            if is_verbose {
-                println!("Successfully opened CSV file.");
-            }
-            f
-        }
-        Err(e) => {
-            eprintln!("ERROR: Could not open file '{}': {}", file_path, e);
-            return Err(Box::new(e));
-        }
-    };
-    let reader = BufReader::new(file);
+}
 
-    let mut rdr = ReaderBuilder::new().has_headers(true).from_reader(reader);
+fn item_name_filter(config: &RunConfig) -> Result<NameFilter, ParseError> {
+    NameFilter::new(
+        ("--item-filter", &config.item_filters),
+        ("--item-exclude", &config.item_excludes),
+    )
+}
 
-    let mut item_data: HashMap<String, ItemStats> = HashMap::new();
-    let mut all_trade_dates: Vec<DateTime<FixedOffset>> = Vec::new();
-    let mut processed_records_count = 0;
-    let mut skipped_records_count = 0;
+fn compile_patterns(flag: &str, patterns: &[String]) -> Result<Vec<Regex>, ParseError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(pattern).map_err(|e| {
+                ParseError::InvalidArgument(format!("Invalid {} '{}': {}", flag, pattern, e))
+            })
+        })
+        .collect()
+}
 
-    if is_verbose {
-        println!("Loading item keywords...");
+fn override_regex(
+    name: &str,
+    pattern: &Option<String>,
+    default: &Regex,
+) -> Result<Regex, ParseError> {
+    match pattern {
+        Some(pattern) => Regex::new(pattern)
+            .map_err(|e| ParseError::InvalidArgument(format!("Invalid {}: {}", name, e))),
+        None => Ok(default.clone()),
     }
-    let item_keywords = items::get_item_keywords();
-    if is_verbose {
-        println!("Item keywords loaded successfully.");
+}
+
+fn validate_date_format(format: &Option<String>) -> Result<Option<String>, ParseError> {
+    if let Some(format) = format
+        && StrftimeItems::new(format).parse().is_err()
+    {
+        return Err(ParseError::InvalidArgument(format!(
+            "Invalid --date-format '{}': not a valid chrono format string.",
+            format
+        )));
     }
+    Ok(format.clone())
+}
 
-    let price_regex = Regex::new(r"(\d[\d\.]*[kK]?|\d[\d,\.]*)").unwrap();
-    let sell_regex = Regex::new(r"(?i)\b(sell|selling|wts)\b").unwrap();
-    let buy_regex = Regex::new(r"(?i)\b(buy|buying|wtb)\b").unwrap();
+/// Hash of a record's author, date and content, which together identify a message
+/// across exports. Cheap enough to run before any regex work.
+fn record_hash(record: &TradeRecord) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    record.author_id.hash(&mut hasher);
+    record.date.hash(&mut hasher);
+    record
+        .content
+        .as_deref()
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
 
-    if is_verbose {
-        println!("Starting to deserialize and process CSV records...");
+/// Flags an author re-posting the same item at the same price within a short window.
+struct SpamFilter {
+    window: Duration,
+    last_posts: HashMap<AuthorId, (ItemName, Price, DateTime<FixedOffset>)>,
+}
+
+impl SpamFilter {
+    fn new(window_secs: u64) -> Self {
+        SpamFilter {
+            window: Duration::seconds(window_secs as i64),
+            last_posts: HashMap::new(),
+        }
     }
-    for (i, result) in rdr.deserialize().enumerate() {
-        let record: TradeRecord = match result {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!(
-                    "WARNING: Skipping malformed record on line {}: {}",
-                    i + 2,
-                    e
-                );
-                skipped_records_count += 1;
-                continue;
-            }
-        };
-        processed_records_count += 1;
 
-        let content = if let Some(c) = record.content {
-            c
-        } else {
-            if is_verbose {
-                println!(
-                    "Skipping record {} (Author: {}): Missing content.",
-                    i + 2,
-                    record.author
-                );
-            }
-            skipped_records_count += 1;
-            continue;
-        };
-        let content_lower = content.to_lowercase();
+    /// Returns `true` when this post repeats the author's previous one within the
+    /// window. Every post is remembered, so a burst of repeats is caught as a chain.
+    fn check_and_record(
+        &mut self,
+        author_id: AuthorId,
+        item_name: &ItemName,
+        price: Price,
+        date: DateTime<FixedOffset>,
+    ) -> bool {
+        let is_spam =
+            self.last_posts
+                .get(&author_id)
+                .is_some_and(|(last_item, last_price, last_date)| {
+                    last_item == item_name
+                        && *last_price == price
+                        && (date - *last_date).abs() <= self.window
+                });
+        self.last_posts
+            .insert(author_id, (item_name.clone(), price, date));
+        is_spam
+    }
+}
 
-        let parsed_date = DateTime::parse_from_rfc3339(&record.date);
-        let trade_date = match parsed_date {
-            Ok(dt) => {
-                all_trade_dates.push(dt);
-                dt
-            }
-            Err(_) => {
-                if is_verbose {
-                    println!(
-                        "Skipping record {} (Author: {}): Unparseable date format '{}'.",
-                        i + 2,
-                        record.author,
-                        record.date
-                    );
-                }
-                skipped_records_count += 1;
-                continue;
+/// A deserialized record waiting to be classified, with its origin for log messages.
+struct PendingRecord {
+    /// Index into `TradeCollector::sources`.
+    source: usize,
+    line: usize,
+    record: TradeRecord,
+}
+
+/// The trade facts extracted from one record.
+struct Observation {
+    item_name: ItemName,
+    prices: Vec<Price>,
+    /// Units per listing, from a pack size like "10x"; 1 when none is given.
+    quantity: u32,
+    is_range: bool,
+    /// The poster invited offers ("obo", "negotiable").
+    is_flexible: bool,
+    /// The poster ruled out haggling ("firm", "no offers").
+    is_firm: bool,
+    condition: Condition,
+    trade_date: DateTime<FixedOffset>,
+    post_kind: PostKind,
+}
+
+/// Why a record did not produce an observation. Items dropped as a whole (by
+/// `--min-posts` and the like) are counted per item in the metadata instead.
+#[derive(Debug, Clone)]
+pub(crate) enum SkipReason {
+    /// The CSV row could not be deserialized.
+    Malformed,
+    /// A repeat of a record already seen, usually from overlapping exports.
+    Duplicate,
+    /// Excluded by `--author-filter` or `--author-exclude`.
+    AuthorFiltered,
+    MissingContent,
+    /// Looked like an RFC 3339 timestamp but wasn't a valid one.
+    UnparseableRfc3339(String),
+    /// Matched none of the supported date formats.
+    UnparseableDate(String),
+    /// Dated outside `--from`/`--to`.
+    DateRangeFiltered(NaiveDate),
+    NoItemFound,
+    NoPriceFound(String),
+    /// The author repeated the same item and price within `spam_window_secs`.
+    SpamDetected,
+    /// Every price was outside `--min-price`/`--max-price`.
+    PriceFiltered,
+}
+
+impl SkipReason {
+    /// The key this reason is counted under in `skipped_by_reason`.
+    fn key(&self) -> &'static str {
+        match self {
+            SkipReason::Malformed => "malformed_record",
+            SkipReason::Duplicate => "duplicate",
+            SkipReason::AuthorFiltered => "author_filtered",
+            SkipReason::MissingContent => "missing_content",
+            SkipReason::UnparseableRfc3339(_) | SkipReason::UnparseableDate(_) => {
+                "unparseable_date"
             }
-        };
+            SkipReason::DateRangeFiltered(_) => "date_range_filtered",
+            SkipReason::NoItemFound => "no_item_found",
+            SkipReason::NoPriceFound(_) => "no_price_found",
+            SkipReason::SpamDetected => "spam_detected",
+            SkipReason::PriceFiltered => "price_filtered",
+        }
+    }
 
-        let mut found_item_name: Option<String> = None;
-        for (item_name, regexes) in &item_keywords {
-            for re in regexes {
-                if re.is_match(&content_lower) {
-                    found_item_name = Some(item_name.clone());
-                    break;
-                }
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            SkipReason::Malformed => "Malformed CSV record".to_string(),
+            SkipReason::Duplicate => "Duplicate of an earlier record".to_string(),
+            SkipReason::AuthorFiltered => "Excluded by the author filter".to_string(),
+            SkipReason::MissingContent => "Missing content".to_string(),
+            SkipReason::UnparseableRfc3339(date) => {
+                format!("Malformed RFC 3339 date '{}' (unparseable_rfc3339)", date)
             }
-            if found_item_name.is_some() {
-                break;
+            SkipReason::UnparseableDate(date) => format!(
+                "Date '{}' matches no known format (unparseable_any_format)",
+                date
+            ),
+            SkipReason::DateRangeFiltered(day) => {
+                format!("Date {} is outside the requested window", day)
             }
+            SkipReason::NoItemFound => "No identifiable item found in content".to_string(),
+            SkipReason::NoPriceFound(item) => format!("No valid price found for item '{}'", item),
+            SkipReason::SpamDetected => "Repeat of their post within the spam window".to_string(),
+            SkipReason::PriceFiltered => "Every price is outside the allowed range".to_string(),
         }
+    }
+}
 
-        let item_name = match found_item_name {
-            Some(name) => name,
-            None => {
-                if is_verbose {
-                    println!(
-                        "Skipping record {} (Author: {}): No identifiable item found in content.",
-                        i + 2,
-                        record.author
-                    );
-                }
-                skipped_records_count += 1;
-                continue;
-            }
+enum RecordOutcome {
+    /// One observation per item the post prices.
+    Observed(Vec<Observation>),
+    /// `trade_date` is set when the record's date was accepted before it was skipped.
+    Skipped {
+        reason: SkipReason,
+        trade_date: Option<DateTime<FixedOffset>>,
+    },
+}
+
+/// How a trade post was classified from its keywords.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PostKind {
+    Completed,
+    Supply,
+    Demand,
+    Swap,
+    Untagged,
+}
+
+#[derive(Debug, Default)]
+struct AuthorStats {
+    author: String,
+    posts_analyzed: u32,
+    items_traded: BTreeSet<ItemName>,
+    price_sum: f64,
+    price_count: u32,
+    supply_posts: u32,
+    demand_posts: u32,
+}
+
+impl ItemStats {
+    /// Drops prices outside `Q1 - 1.5*IQR ..= Q3 + 1.5*IQR`, keeping `unit_prices`,
+    /// `trade_dates`, `post_kinds`, `conditions`, `authors` and the per-side price
+    /// lists in step. Needs at least four prices to establish quartiles.
+    fn reject_outliers(&mut self) {
+        if self.prices.len() < 4 {
+            return;
+        }
+        let mut sorted = self.prices.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let (Some(q1), Some(q3)) = (
+            price_at_percentile(&sorted, 25.0),
+            price_at_percentile(&sorted, 75.0),
+        ) else {
+            return;
         };
+        let fence = 1.5 * (q3 - q1);
+        let (low, high) = (q1 - fence, q3 + fence);
+
+        let kept: Vec<usize> = (0..self.prices.len())
+            .filter(|&i| (low..=high).contains(&self.prices[i]))
+            .collect();
+        let before = self.prices.len();
+        self.prices = kept.iter().map(|&i| self.prices[i]).collect();
+        self.trade_dates = kept.iter().map(|&i| self.trade_dates[i]).collect();
+        self.unit_prices = kept.iter().map(|&i| self.unit_prices[i]).collect();
+        self.post_kinds = kept.iter().map(|&i| self.post_kinds[i]).collect();
+        self.conditions = kept.iter().map(|&i| self.conditions[i]).collect();
+        self.authors = kept.iter().map(|&i| self.authors[i].clone()).collect();
+        self.supply_prices = self.prices_of_kind(PostKind::Supply);
+        self.demand_prices = self.prices_of_kind(PostKind::Demand);
+        self.outliers_removed += (before - self.prices.len()) as u32;
+    }
 
-        let price_str = price_regex.find(&content_lower);
-        let price = if let Some(m) = price_str {
-            let mut p_str = m.as_str().replace('$', "").replace(',', "");
-            if p_str.ends_with('k') || p_str.ends_with('K') {
-                p_str.pop();
-                p_str.parse::<f64>().ok().map(|val| val * 1000.0)
+    /// Folds `other` into `self`, as if its records had been read into the same
+    /// stats: counters are summed and the price-aligned lists are interleaved by
+    /// trade date, so two date-ordered stats stay date-ordered without a re-sort.
+    /// On equal dates `self`'s prices come first.
+    pub fn merge(&mut self, other: ItemStats) {
+        // `true` takes the next entry from `other`, `false` from `self`.
+        let mut order = Vec::with_capacity(self.prices.len() + other.prices.len());
+        let (mut i, mut j) = (0, 0);
+        while i < self.trade_dates.len() && j < other.trade_dates.len() {
+            let from_other = other.trade_dates[j] < self.trade_dates[i];
+            order.push(from_other);
+            if from_other {
+                j += 1;
             } else {
-                p_str.parse::<f64>().ok()
+                i += 1;
             }
-        } else {
-            None
+        }
+        order.extend(std::iter::repeat_n(false, self.trade_dates.len() - i));
+        order.extend(std::iter::repeat_n(true, other.trade_dates.len() - j));
+
+        self.prices = interleave(&order, std::mem::take(&mut self.prices), other.prices);
+        self.unit_prices = interleave(
+            &order,
+            std::mem::take(&mut self.unit_prices),
+            other.unit_prices,
+        );
+        self.trade_dates = interleave(
+            &order,
+            std::mem::take(&mut self.trade_dates),
+            other.trade_dates,
+        );
+        self.post_kinds = interleave(
+            &order,
+            std::mem::take(&mut self.post_kinds),
+            other.post_kinds,
+        );
+        self.conditions = interleave(
+            &order,
+            std::mem::take(&mut self.conditions),
+            other.conditions,
+        );
+        self.authors = interleave(&order, std::mem::take(&mut self.authors), other.authors);
+        self.supply_prices = self.prices_of_kind(PostKind::Supply);
+        self.demand_prices = self.prices_of_kind(PostKind::Demand);
+
+        self.supply_posts += other.supply_posts;
+        self.demand_posts += other.demand_posts;
+        self.swap_posts += other.swap_posts;
+        self.completed_posts += other.completed_posts;
+        for (condition, posts) in other.condition_breakdown {
+            *self.condition_breakdown.entry(condition).or_default() += posts;
+        }
+        self.range_derived_posts += other.range_derived_posts;
+        self.flexible_posts += other.flexible_posts;
+        self.firm_posts += other.firm_posts;
+        self.outliers_removed += other.outliers_removed;
+    }
+
+    /// The stats as compact JSON, `{"version":1,"stats":{...}}`, for checkpointing
+    /// an incremental pipeline in a file or key-value store between runs.
+    pub fn snapshot(&self) -> String {
+        serde_json::to_string(&SnapshotRef {
+            version: SNAPSHOT_VERSION,
+            stats: self,
+        })
+        .expect("item stats have only string map keys, so they always serialize")
+    }
+
+    /// Reads back a [`snapshot`](ItemStats::snapshot). Fields missing from the
+    /// snapshot are left empty; a snapshot in any other format version, or whose
+    /// per-price lists differ in length, is refused.
+    pub fn restore(snapshot: &str) -> Result<ItemStats, ParseError> {
+        let unreadable = |e: serde_json::Error| {
+            ParseError::InvalidArgument(format!("Could not read the ItemStats snapshot: {}", e))
         };
+        let snapshot: Snapshot = serde_json::from_str(snapshot).map_err(unreadable)?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            return Err(ParseError::InvalidArgument(format!(
+                "Unsupported ItemStats snapshot version {}; this build reads version {}.",
+                snapshot.version, SNAPSHOT_VERSION
+            )));
+        }
+        let stats: ItemStats = serde_json::from_value(snapshot.stats).map_err(unreadable)?;
+        // `merge` and the statistics index these lists together, so they must line up.
+        let lengths = [
+            stats.prices.len(),
+            stats.unit_prices.len(),
+            stats.trade_dates.len(),
+            stats.post_kinds.len(),
+            stats.conditions.len(),
+            stats.authors.len(),
+        ];
+        if lengths.iter().any(|&len| len != lengths[0]) {
+            return Err(ParseError::InvalidArgument(format!(
+                "Could not read the ItemStats snapshot: its prices, unit_prices, trade_dates, \
+                 post_kinds, conditions and authors have {:?} entries, which must all match.",
+                lengths
+            )));
+        }
+        Ok(stats)
+    }
 
-        let price_val = match price {
-            Some(p) => p,
-            None => {
-                if is_verbose {
-                    println!(
-                        "Skipping record {} (Author: {}): No valid price found for item '{}'.",
-                        i + 2,
-                        record.author,
-                        item_name
-                    );
-                }
-                skipped_records_count += 1;
-                continue;
+    fn prices_in_condition(&self, condition: Condition) -> Vec<f64> {
+        self.prices
+            .iter()
+            .zip(&self.conditions)
+            .filter(|(_, price_condition)| **price_condition == condition)
+            .map(|(price, _)| *price)
+            .collect()
+    }
+
+    fn prices_of_kind(&self, kind: PostKind) -> Vec<f64> {
+        self.prices
+            .iter()
+            .zip(&self.post_kinds)
+            .filter(|(_, post_kind)| **post_kind == kind)
+            .map(|(price, _)| *price)
+            .collect()
+    }
+}
+
+/// The current [`ItemStats::snapshot`] format; bump it when the stats' fields change
+/// in a way older snapshots cannot be read as.
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    version: u32,
+    stats: &'a ItemStats,
+}
+
+/// A snapshot whose `stats` are only parsed once its version is known.
+#[derive(Deserialize)]
+struct Snapshot {
+    version: u32,
+    stats: serde_json::Value,
+}
+
+/// Builds one list from `mine` and `theirs` in the order [`ItemStats::merge`]
+/// picked, where `true` takes the next entry of `theirs`.
+fn interleave<T>(order: &[bool], mine: Vec<T>, theirs: Vec<T>) -> Vec<T> {
+    let (mut mine, mut theirs) = (mine.into_iter(), theirs.into_iter());
+    order
+        .iter()
+        .filter_map(|&from_theirs| {
+            if from_theirs {
+                theirs.next()
+            } else {
+                mine.next()
             }
-        };
+        })
+        .collect()
+}
 
-        let stats = item_data.entry(item_name).or_default();
-        stats.prices.push(price_val);
-        stats.trade_dates.push(trade_date);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimatedPrice {
+    pub median: Option<f64>,
+    /// Arithmetic mean of the prices.
+    pub mean: Option<f64>,
+    /// `(mean - median) / median * 100`. A large positive skew means a few high
+    /// prices pull the mean up, so budget by the median instead.
+    pub mean_vs_median_skew: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub variance: Option<f64>,
+    pub std_dev: Option<f64>,
+    /// Median with each price weighted by `exp(-decay_rate * days_old)`, favouring recent trades.
+    pub weighted_median: Option<f64>,
+    pub p25: Option<f64>,
+    pub p75: Option<f64>,
+    /// Interquartile range (`p75 - p25`).
+    pub iqr: Option<f64>,
+    /// True when at least one observation came from a quoted price range.
+    pub is_range_derived: bool,
+    /// Median of each price divided by its pack size ("10x bolts for 500" is 50
+    /// a unit); equal to `median` when nothing was sold in packs.
+    pub median_unit_price: Option<f64>,
+    /// Median of prices quoted for new or brand-new items.
+    pub new_median: Option<f64>,
+    /// Median of prices quoted for used, worn or damaged items.
+    pub used_median: Option<f64>,
+}
 
-        if sell_regex.is_match(&content_lower) {
-            stats.supply_posts += 1;
-        } else if buy_regex.is_match(&content_lower) {
-            stats.demand_posts += 1;
-        }
+/// Compares every field by bit pattern, so two identical runs compare equal even
+/// when a statistic is `NaN`, while `0.0` and `-0.0` are told apart.
+impl PartialEq for EstimatedPrice {
+    fn eq(&self, other: &Self) -> bool {
+        let same = |a: Option<f64>, b: Option<f64>| a.map(f64::to_bits) == b.map(f64::to_bits);
+        same(self.median, other.median)
+            && same(self.mean, other.mean)
+            && same(self.mean_vs_median_skew, other.mean_vs_median_skew)
+            && same(self.min, other.min)
+            && same(self.max, other.max)
+            && same(self.variance, other.variance)
+            && same(self.std_dev, other.std_dev)
+            && same(self.weighted_median, other.weighted_median)
+            && same(self.p25, other.p25)
+            && same(self.p75, other.p75)
+            && same(self.iqr, other.iqr)
+            && self.is_range_derived == other.is_range_derived
+            && same(self.median_unit_price, other.median_unit_price)
+            && same(self.new_median, other.new_median)
+            && same(self.used_median, other.used_median)
     }
-    if is_verbose {
-        println!(
-            "Finished processing {} records ({} skipped).",
-            processed_records_count, skipped_records_count
-        );
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SupplyDemand {
+    pub supply_posts: u32,
+    pub demand_posts: u32,
+    /// Want-to-trade posts; counted separately and excluded from trade chances.
+    pub swap_posts: u32,
+    /// Posts announcing a finished trade ("sold", "deal done").
+    pub completed_posts: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TradeChance {
+    pub chance_to_buy: String,
+    pub chance_to_sell: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemAnalysis {
+    pub item: String,
+    pub estimated_price: EstimatedPrice,
+    /// Heuristic "what should this sell for": the supply and demand medians, each
+    /// weighted by the other side's share of posts, so heavy supply pulls the value
+    /// toward what buyers offer. Falls back to the overall median.
+    pub fair_value: Option<f64>,
+    pub supply_demand: SupplyDemand,
+    pub estimated_trade_chances: TradeChance,
+    pub rough_selling_frequency: String,
+    /// Completed ("sold") posts per supply post; `None` without any supply posts.
+    pub trade_completion_rate: Option<f64>,
+    /// RFC 3339 timestamp of the item's earliest observation.
+    pub first_seen: Option<String>,
+    /// RFC 3339 timestamp of the item's latest observation.
+    pub last_seen: Option<String>,
+    /// Days between `first_seen` and `last_seen`. A long span with few posts marks a
+    /// slow market, a short one with many posts an event-driven one.
+    pub observation_span_days: Option<f64>,
+    pub last_trade_date: Option<String>,
+    pub days_since_last_trade: Option<f64>,
+    /// `"active"`, `"dormant"` or `"inactive"` depending on `days_since_last_trade`.
+    pub activity_status: String,
+    /// Prices dropped by `--reject-outliers`; always 0 when the filter is off.
+    pub outliers_removed: u32,
+    /// `"high"`, `"medium"`, `"low"` or `"insufficient"`, by number of prices
+    /// observed (20+, 5+ and 2+ by default).
+    pub confidence_tier: String,
+    /// Sum of the item's listed prices, a proxy for its market turnover.
+    pub total_volume: f64,
+    /// Median supply-post price minus median demand-post price: a rough bid-ask
+    /// spread. `None` unless the item has both supply and demand prices.
+    pub estimated_spread: Option<f64>,
+    /// `estimated_spread` as a percentage of the median supply price.
+    pub spread_percentage: Option<f64>,
+    /// `"wide_market"` when the spread exceeds 20% either way, `"normal"` otherwise,
+    /// `"unknown"` without a spread.
+    pub market_condition: String,
+    /// `"mostly_flexible"` when more posts invite offers ("obo", "negotiable") than
+    /// rule them out ("firm", "no offers"), `"mostly_firm"` for the reverse, `"mixed"`
+    /// on a tie and `"unknown"` when no post says either.
+    #[serde(default)]
+    pub typical_price_flexibility: String,
+    /// Posts per condition (`"new"`, `"near_mint"`, `"used"` or `"unknown"`), taken
+    /// from the first condition keyword in each post.
+    #[serde(default)]
+    pub condition_breakdown: BTreeMap<String, u32>,
+    /// The latest week saw more than 3x the weekly average of supply posts over the
+    /// three weeks before it, e.g. someone bulk-listing.
+    pub supply_shock: bool,
+    /// The same test as `supply_shock`, applied to demand posts.
+    pub demand_spike: bool,
+    /// Supply posts per demand post; above 1.0 sellers outnumber buyers.
+    /// `None` without any demand posts.
+    pub market_saturation: Option<f64>,
+    /// Volume times posts per day, scaled so the most liquid item scores 100.
+    pub liquidity_score: f64,
+    /// 1 for the item with the most supply plus demand posts, ties going to the
+    /// name that sorts first. Out of `total_items_in_dataset`; 0 in streamed JSONL.
+    #[serde(default)]
+    pub popularity_rank: usize,
+    /// Mean price over the 7 days up to the item's latest trade.
+    pub rolling_avg_7d: Option<f64>,
+    /// Mean price over the 30 days up to the item's latest trade.
+    pub rolling_avg_30d: Option<f64>,
+    /// Percentage change from the median price of the 30 days before the last 30
+    /// to the median of the last 30, counted back from the run; positive when
+    /// rising. `None` unless both windows saw a trade.
+    #[serde(default)]
+    pub momentum_30d: Option<f64>,
+    /// The day of the week with the most price observations, Monday winning ties.
+    pub busiest_day: Option<String>,
+    /// `"rising"`, `"stable"` or `"falling"`, comparing the mean of the older half of
+    /// the prices with the newer half; `"insufficient_data"` below four prices.
+    pub price_trend: String,
+    /// Share of the prices, 0.0 to 1.0, equal to `dominant_price` to the cent.
+    #[serde(default)]
+    pub price_uniformity: f64,
+    /// The most common price to the cent, the lowest winning ties.
+    #[serde(default)]
+    pub dominant_price: Option<f64>,
+    /// `"high_uniformity_may_indicate_spam_or_fixed_price"` when more than 70% of
+    /// at least five prices are the same, a fixed-price norm or a spammer.
+    #[serde(default)]
+    pub data_quality_warning: Option<String>,
+    /// Price observations per UTC hour. Only filled in with `--include-hourly`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hourly_activity: Option<[u32; 24]>,
+    /// Every individual price, oldest first. Only filled in with `--include-history`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub price_history: Vec<PricePoint>,
+}
+
+impl ItemAnalysis {
+    /// The same one-line summary as the `Display` impl.
+    pub fn to_summary_line(&self) -> String {
+        self.to_string()
     }
+}
 
-    let overall_parsing_time = start_time.elapsed();
+impl fmt::Display for ItemAnalysis {
+    /// `Primitive Deck: median=$3000 [min=$1500, max=$5000] supply=12 demand=7 (57.89% sell chance)`,
+    /// with `n/a` for a price that wasn't observed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let price = |value: Option<f64>| value.map_or("n/a".to_string(), |v| format!("${}", v));
+        write!(
+            f,
+            "{}: median={} [min={}, max={}] supply={} demand={} ({} sell chance)",
+            self.item,
+            price(self.estimated_price.median),
+            price(self.estimated_price.min),
+            price(self.estimated_price.max),
+            self.supply_demand.supply_posts,
+            self.supply_demand.demand_posts,
+            self.estimated_trade_chances.chance_to_sell
+        )
+    }
+}
 
-    all_trade_dates.sort();
+/// A single observed price, for charting an item over time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricePoint {
+    /// RFC 3339 timestamp of the post.
+    pub date: String,
+    pub price: f64,
+    /// `"sell"` (including completed sales), `"buy"`, `"swap"`, or `"untagged"`
+    /// when the post carried no trade keyword.
+    pub direction: String,
+    /// The pack size `price` was listed for, when the post named one above 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<u32>,
+    /// `"new"`, `"near_mint"` or `"used"`; left out when the post named no condition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// Display name of the author who posted the price; empty in analyses saved
+    /// before authors were recorded.
+    #[serde(default)]
+    pub author: String,
+}
 
-    let earliest_message_utc_epoch = all_trade_dates.first().map(|dt| dt.timestamp());
-    let latest_message_utc_epoch = all_trade_dates.last().map(|dt| dt.timestamp());
-    let parser_run_utc_epoch = Utc::now().timestamp();
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorAnalysis {
+    pub author_id: u64,
+    pub author: String,
+    pub posts_analyzed: u32,
+    pub items_traded: Vec<String>,
+    pub avg_price_listed: Option<f64>,
+    /// Sum of every price the author listed, across all their posts.
+    #[serde(default)]
+    pub total_value_listed: f64,
+    pub supply_posts: u32,
+    pub demand_posts: u32,
+}
 
-    let total_duration = if all_trade_dates.len() > 1 {
-        all_trade_dates
-            .last()
-            .unwrap()
-            .signed_duration_since(*all_trade_dates.first().unwrap())
-    } else {
-        Duration::zero()
-    };
-    let total_days = total_duration.num_days() as f64;
-    let total_weeks = total_duration.num_weeks() as f64;
-    let total_months = total_days / 30.44;
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisOutput {
+    pub total_parsing_time_ms: u128,
+    pub overall_trade_data_span_days: f64,
+    pub overall_trade_data_span_weeks: f64,
+    pub overall_trade_data_span_months: f64,
+    /// Sum of every listed price across all items, after outlier rejection.
+    pub global_volume: f64,
+    /// `--currency`, as given; prefixes the prices in the metadata lines and
+    /// Markdown tables. Empty when unset.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub currency: String,
+    /// Average of the reported items' median prices, each weighted by its supply
+    /// plus demand posts, as a "market temperature" to track between runs.
+    /// `None` when no item has both a median and posts; never set for streamed JSONL.
+    #[serde(default)]
+    pub market_index: Option<f64>,
+    /// Like `market_index`, but weighted by supply posts alone.
+    #[serde(default)]
+    pub index_by_supply_only: Option<f64>,
+    /// Like `market_index`, but weighted by demand posts alone.
+    #[serde(default)]
+    pub index_by_demand_only: Option<f64>,
+    /// How many items the output reports, the "of M" in each `popularity_rank`.
+    #[serde(default)]
+    pub total_items_in_dataset: usize,
+    /// Estimated market turnover per week: each reported item's supply plus demand
+    /// posts per week times its median price, summed. `None` when the data spans
+    /// less than a day; never set for streamed JSONL.
+    #[serde(default)]
+    pub estimated_weekly_volume_usd: Option<f64>,
+    /// In-window messages per day of the week, `"Monday"` through `"Sunday"`.
+    #[serde(default, serialize_with = "serialize_weekly_pattern")]
+    pub weekly_pattern: HashMap<String, u32>,
+    /// Average messages per weekend day over average per weekday; above 1.0 the
+    /// weekend is busier. 0.0 when no weekday messages were seen.
+    #[serde(default)]
+    pub weekend_to_weekday_ratio: f64,
+    /// In-window messages per UTC hour, index 0 being midnight to 1am.
+    #[serde(default)]
+    pub hourly_activity: [u32; 24],
+    /// The UTC hour with the most messages, the earliest winning ties.
+    #[serde(default)]
+    pub peak_hour_utc: Option<u8>,
+    pub items: Vec<ItemAnalysis>,
+    /// Per-author activity, most active first; empty when disabled with `--no-authors`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<AuthorAnalysis>,
+    /// Run metadata, rendered as YAML comments or a JSON `"metadata"` key
+    /// rather than as a regular field.
+    #[serde(skip)]
+    pub metadata: AnalysisMetadata,
+}
 
-    let data_display_period = if all_trade_dates.is_empty() {
-        "No data available".to_string()
-    } else if total_duration.num_seconds() == 0 {
-        "Less than a day (or only one record)".to_string()
-    } else if total_months >= 1.0 {
-        let months = total_duration.num_days() / 30;
-        let remaining_days = total_duration.num_days() % 30;
-        format!("{} months, {} days", months, remaining_days)
-    } else if total_weeks >= 1.0 {
-        let weeks = total_duration.num_days() / 7;
-        let remaining_days = total_duration.num_days() % 7;
-        format!("{} weeks, {} days", weeks, remaining_days)
-    } else {
-        format!("{:.0} days", total_days)
-    };
+impl AnalysisOutput {
+    /// The `n` authors with the most supply posts, for spotting power sellers or
+    /// bot accounts. Ties go to the larger `total_value_listed`, then by name;
+    /// authors who never sold are left out.
+    pub fn top_sellers(&self, n: usize) -> Vec<AuthorAnalysis> {
+        self.top_authors(n, |author| author.supply_posts)
+    }
 
-    if all_trade_dates.is_empty() {
-        println!(
-            "\nWARNING: No valid trade data found after parsing. Output will contain no item analysis."
-        );
+    /// Like [`top_sellers`](Self::top_sellers), but by demand posts.
+    pub fn top_buyers(&self, n: usize) -> Vec<AuthorAnalysis> {
+        self.top_authors(n, |author| author.demand_posts)
     }
 
-    if is_verbose {
-        println!("\nAggregating and sorting item data...");
-    }
-    let mut results: Vec<ItemAnalysis> = Vec::new();
-
-    let mut sorted_item_data: Vec<(String, ItemStats)> = item_data.into_iter().collect();
-    sorted_item_data.sort_by(|a, b| {
-        let median_a = if !a.1.prices.is_empty() {
-            let mut prices = a.1.prices.clone();
-            prices.sort_by(|x, y| x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal));
-            let mid = prices.len() / 2;
-            if prices.len() % 2 == 0 {
-                (prices[mid - 1] + prices[mid]) / 2.0
-            } else {
-                prices[mid]
-            }
-        } else {
-            0.0
-        };
-        let median_b = if !b.1.prices.is_empty() {
-            let mut prices = b.1.prices.clone();
-            prices.sort_by(|x, y| x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal));
-            let mid = prices.len() / 2;
-            if prices.len() % 2 == 0 {
-                (prices[mid - 1] + prices[mid]) / 2.0
-            } else {
-                prices[mid]
-            }
-        } else {
-            0.0
-        };
-        median_b
-            .partial_cmp(&median_a)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+    fn top_authors(&self, n: usize, posts: impl Fn(&AuthorAnalysis) -> u32) -> Vec<AuthorAnalysis> {
+        let mut authors: Vec<&AuthorAnalysis> = self
+            .authors
+            .iter()
+            .filter(|author| posts(author) > 0)
+            .collect();
+        authors.sort_by(|a, b| {
+            posts(b)
+                .cmp(&posts(a))
+                .then_with(|| b.total_value_listed.total_cmp(&a.total_value_listed))
+                .then_with(|| a.author.cmp(&b.author))
+        });
+        authors.into_iter().take(n).cloned().collect()
+    }
+}
 
-    for (item_name, mut stats) in sorted_item_data {
-        stats
-            .prices
-            .sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisMetadata {
+    pub earliest_message_utc_epoch: Option<i64>,
+    pub latest_message_utc_epoch: Option<i64>,
+    pub parser_run_utc_epoch: i64,
+    pub csv_data_time_period: String,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub out_of_range_records_count: u32,
+    pub input_files: Vec<InputFileSummary>,
+    /// Records that were malformed or gave no trade observation, such as posts
+    /// naming no item or price.
+    pub skipped_records_count: u32,
+    /// Item observations that made it into the item statistics; a post naming
+    /// several items gives one per priced item.
+    pub observations_count: u32,
+    /// Posts that named and priced more than one item, such as "selling primitive
+    /// deck 3000 and some wheels 800".
+    #[serde(default)]
+    pub multi_item_posts_count: u32,
+    /// Prices dropped for falling outside `--min-price`/`--max-price`.
+    #[serde(default)]
+    pub price_filtered_count: u32,
+    /// Every record that did not become an observation, counted by why: for example
+    /// `no_item_found`, `no_price_found`, `duplicate` or `date_range_filtered`.
+    #[serde(default)]
+    pub skipped_by_reason: BTreeMap<String, u32>,
+    pub duplicate_records_count: u32,
+    /// Posts dropped as an author repeating the same item and price within `spam_window_secs`.
+    pub spam_filtered_count: u32,
+    /// Records skipped by `--author-filter` or `--author-exclude`.
+    pub author_filtered_count: u32,
+    /// Items left out by `--item-filter` or `--item-exclude`.
+    pub items_filtered: usize,
+    pub items_suppressed_low_data: usize,
+    /// Items left out because they fell below `--min-confidence`.
+    pub items_below_min_confidence: usize,
+    /// Items left out because `--top` capped the output.
+    pub items_omitted: usize,
+    /// Supply posts per demand post summed over every item, reported or not.
+    pub global_market_saturation: Option<f64>,
+}
 
-        let median_price = if stats.prices.is_empty() {
-            None
-        } else {
-            let mid = stats.prices.len() / 2;
-            Some(if stats.prices.len() % 2 == 0 {
-                (stats.prices[mid - 1] + stats.prices[mid]) / 2.0
-            } else {
-                stats.prices[mid]
+/// Per-file record count reported in the metadata block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct InputFileSummary {
+    pub path: String,
+    pub records: u32,
+}
+
+/// Reloads an analysis saved as YAML (or JSON, which YAML also accepts). The
+/// metadata block is not part of the saved fields, so it comes back as the default.
+pub fn load_analysis(path: &str) -> Result<AnalysisOutput, ParseError> {
+    let file = File::open(path).map_err(ParseError::FileNotFound)?;
+    Ok(serde_yaml::from_reader(BufReader::new(file))?)
+}
+
+/// Combines two saved analyses as if their exports had been parsed together, for
+/// offline merging of monthly runs. Each item's `price_history` is pooled and its
+/// statistics recomputed; post counts, authors and activity counts are summed;
+/// `total_parsing_time_ms` is the sum of both. Both inputs need every item's
+/// `price_history`, so they must have been saved with `--include-history`, and they
+/// should cover different records, as repeats are counted twice.
+pub fn merge_outputs(
+    a: &AnalysisOutput,
+    b: &AnalysisOutput,
+    config: &RunConfig,
+) -> Result<AnalysisOutput, ParseError> {
+    // The merged result keeps its history so it can be merged again.
+    let config = RunConfig {
+        include_history: true,
+        ..config.clone()
+    };
+    let mut item_data: HashMap<ItemName, ItemStats> = HashMap::new();
+    let mut flexibility: HashMap<ItemName, BTreeSet<String>> = HashMap::new();
+    for item in a.items.iter().chain(&b.items) {
+        if item.price_history.is_empty() {
+            return Err(ParseError::InvalidArgument(format!(
+                "Cannot merge '{}': it has no price_history. Save both analyses with --include-history.",
+                item.item
+            )));
+        }
+        let name = ItemName::from(item.item.as_str());
+        item_data
+            .entry(name.clone())
+            .or_default()
+            .merge(saved_item_stats(item)?);
+        if item.typical_price_flexibility != "unknown" {
+            flexibility
+                .entry(name)
+                .or_default()
+                .insert(item.typical_price_flexibility.clone());
+        }
+    }
+    if config.reject_outliers {
+        for stats in item_data.values_mut() {
+            stats.reject_outliers();
+        }
+    }
+
+    let mut all_trade_dates: Vec<DateTime<FixedOffset>> = item_data
+        .values()
+        .flat_map(|stats| stats.trade_dates.iter().copied())
+        .collect();
+    all_trade_dates.sort();
+    let run_time = Utc::now();
+    let context = DatasetContext::new(&item_data, &all_trade_dates, run_time);
+    let global_volume = round_cents(item_data.values().flat_map(|stats| &stats.prices).sum());
+    let global_market_saturation = saturation(
+        item_data.values().map(|stats| stats.supply_posts).sum(),
+        item_data.values().map(|stats| stats.demand_posts).sum(),
+    );
+    let mut items: Vec<ItemAnalysis> =
+        sort_item_stats(item_data, config.sort_key(), config.sort_ascending())
+            .into_iter()
+            .map(|(name, stats)| {
+                // Per-post flexibility isn't saved, so fall back on the inputs' labels.
+                let labels = flexibility.remove(&name).unwrap_or_default();
+                let mut item = analyze_item(name, stats, &config, &context);
+                item.typical_price_flexibility = match labels.len() {
+                    0 => "unknown".to_string(),
+                    1 => labels.into_iter().next().unwrap_or_default(),
+                    _ => "mixed".to_string(),
+                };
+                item
             })
-        };
-        let min_price = stats.prices.first().cloned();
-        let max_price = stats.prices.last().cloned();
+            .collect();
 
-        let total_posts = stats.supply_posts + stats.demand_posts;
+    assign_popularity_ranks(&mut items);
 
-        let buy_chance = if total_posts > 0 && stats.demand_posts > 0 {
-            (stats.demand_posts as f64 / total_posts as f64) * 100.0
-        } else {
-            0.0
-        };
+    // The widest of both inputs' spans, which also counted records without a price.
+    let span_days = (trade_data_span(&all_trade_dates).num_days() as f64)
+        .max(a.overall_trade_data_span_days)
+        .max(b.overall_trade_data_span_days);
+    let weekly_pattern: HashMap<String, u32> = WEEKDAYS
+        .iter()
+        .map(|day| {
+            let name = weekday_name(*day);
+            let count = [a, b]
+                .iter()
+                .filter_map(|output| output.weekly_pattern.get(name))
+                .sum();
+            (name.to_string(), count)
+        })
+        .collect();
+    let weekday_counts = WEEKDAYS.map(|day| weekly_pattern[weekday_name(day)]);
+    let mut hourly_activity = a.hourly_activity;
+    for (count, other) in hourly_activity.iter_mut().zip(b.hourly_activity) {
+        *count += other;
+    }
 
-        let sell_chance = if total_posts > 0 && stats.supply_posts > 0 {
-            (stats.supply_posts as f64 / total_posts as f64) * 100.0
-        } else {
-            0.0
-        };
+    Ok(AnalysisOutput {
+        total_parsing_time_ms: a.total_parsing_time_ms + b.total_parsing_time_ms,
+        overall_trade_data_span_days: span_days,
+        overall_trade_data_span_weeks: (span_days / 7.0).floor(),
+        overall_trade_data_span_months: span_days / 30.44,
+        global_volume,
+        currency: config.currency.clone(),
+        market_index: weighted_market_index(&items, |counts| {
+            counts.supply_posts + counts.demand_posts
+        }),
+        index_by_supply_only: weighted_market_index(&items, |counts| counts.supply_posts),
+        index_by_demand_only: weighted_market_index(&items, |counts| counts.demand_posts),
+        total_items_in_dataset: items.len(),
+        estimated_weekly_volume_usd: estimated_weekly_volume(&items, span_days),
+        weekly_pattern,
+        weekend_to_weekday_ratio: weekend_to_weekday_ratio(&weekday_counts),
+        hourly_activity,
+        peak_hour_utc: peak_hour(&hourly_activity),
+        items,
+        authors: merge_authors(&a.authors, &b.authors),
+        metadata: AnalysisMetadata {
+            earliest_message_utc_epoch: all_trade_dates.first().map(|dt| dt.timestamp()),
+            latest_message_utc_epoch: all_trade_dates.last().map(|dt| dt.timestamp()),
+            parser_run_utc_epoch: run_time.timestamp(),
+            csv_data_time_period: data_period_label(&all_trade_dates),
+            global_market_saturation,
+            ..AnalysisMetadata::default()
+        },
+    })
+}
 
-        let frequency_str = if total_posts > 0 && total_days > 0.0 {
-            let trades_per_day = total_posts as f64 / total_days;
-            if trades_per_day >= 1.0 {
-                format!("{:.2} times/day", trades_per_day)
-            } else if trades_per_day * 7.0 >= 1.0 {
-                format!("{:.2} times/week", trades_per_day * 7.0)
-            } else if trades_per_day * 30.44 >= 1.0 {
-                format!("{:.2} times/month", trades_per_day * 30.44)
-            } else {
-                format!("Once every {:.0} days", 1.0 / trades_per_day)
-            }
-        } else {
-            "Infrequently/Not observed".to_string()
+/// Rebuilds the stats behind a saved item from its price history and counts.
+fn saved_item_stats(item: &ItemAnalysis) -> Result<ItemStats, ParseError> {
+    let mut stats = ItemStats::default();
+    for point in &item.price_history {
+        let date = DateTime::parse_from_rfc3339(&point.date).map_err(|e| {
+            ParseError::InvalidArgument(format!(
+                "Invalid price_history date '{}' for '{}': {}",
+                point.date, item.item, e
+            ))
+        })?;
+        // Completed sales were saved as "sell" too; their count is restored below.
+        let kind = match point.direction.as_str() {
+            "sell" => PostKind::Supply,
+            "buy" => PostKind::Demand,
+            "swap" => PostKind::Swap,
+            _ => PostKind::Untagged,
         };
+        stats.prices.push(point.price);
+        stats
+            .unit_prices
+            .push(point.price / f64::from(point.quantity.unwrap_or(1)));
+        stats.trade_dates.push(date);
+        stats.post_kinds.push(kind);
+        stats.conditions.push(
+            point
+                .condition
+                .as_deref()
+                .map_or(Condition::Unknown, Condition::from_name),
+        );
+        stats.authors.push(point.author.clone());
+        match kind {
+            PostKind::Supply => stats.supply_prices.push(point.price),
+            PostKind::Demand => stats.demand_prices.push(point.price),
+            _ => {}
+        }
+    }
+    let counts = &item.supply_demand;
+    stats.supply_posts += counts.supply_posts;
+    stats.demand_posts += counts.demand_posts;
+    stats.swap_posts += counts.swap_posts;
+    stats.completed_posts += counts.completed_posts;
+    for (condition, posts) in &item.condition_breakdown {
+        *stats
+            .condition_breakdown
+            .entry(condition.clone())
+            .or_default() += posts;
+    }
+    stats.range_derived_posts += u32::from(item.estimated_price.is_range_derived);
+    stats.outliers_removed += item.outliers_removed;
+    Ok(stats)
+}
 
-        results.push(ItemAnalysis {
-            item: item_name,
-            estimated_price: EstimatedPrice {
-                median: median_price,
-                min: min_price,
-                max: max_price,
-            },
-            supply_demand: SupplyDemand {
-                supply_posts: stats.supply_posts,
-                demand_posts: stats.demand_posts,
-            },
-            estimated_trade_chances: TradeChance {
-                chance_to_buy: format!("{:.2}%", buy_chance),
-                chance_to_sell: format!("{:.2}%", sell_chance),
-            },
-            rough_selling_frequency: frequency_str,
-        });
+/// Sums each author's activity across both runs, most active first.
+fn merge_authors(a: &[AuthorAnalysis], b: &[AuthorAnalysis]) -> Vec<AuthorAnalysis> {
+    let mut merged: HashMap<u64, AuthorAnalysis> = HashMap::new();
+    for author in a.iter().chain(b) {
+        let Some(existing) = merged.get_mut(&author.author_id) else {
+            merged.insert(author.author_id, author.clone());
+            continue;
+        };
+        // Weighted by posts, as the saved averages don't record their price counts.
+        let posts = existing.posts_analyzed + author.posts_analyzed;
+        existing.avg_price_listed = match (existing.avg_price_listed, author.avg_price_listed) {
+            (Some(x), Some(y)) => Some(
+                (x * f64::from(existing.posts_analyzed) + y * f64::from(author.posts_analyzed))
+                    / f64::from(posts),
+            ),
+            (x, y) => x.or(y),
+        };
+        existing.posts_analyzed = posts;
+        existing.total_value_listed =
+            round_cents(existing.total_value_listed + author.total_value_listed);
+        existing.supply_posts += author.supply_posts;
+        existing.demand_posts += author.demand_posts;
+        let items: BTreeSet<String> = existing
+            .items_traded
+            .drain(..)
+            .chain(author.items_traded.iter().cloned())
+            .collect();
+        existing.items_traded = items.into_iter().collect();
     }
-    if is_verbose {
-        println!("Item data aggregation complete.");
-    }
-
-    let metadata_comments = format!(
-        "# Trade Analysis Metadata\n\
-        # ------------------------\n\
-        # Earliest message (UTC Epoch): {}\n\
-        # Latest message (UTC Epoch): {}\n\
-        # Parser run time (UTC Epoch): {}\n\
-        # CSV data time period: {}\n\
-        # Total parsing and processing time: {} ms\n\
-        # Overall trade data span: {:.2} days ({:.2} weeks, {:.2} months)\n\n",
-        earliest_message_utc_epoch.map_or("N/A".to_string(), |e| e.to_string()),
-        latest_message_utc_epoch.map_or("N/A".to_string(), |e| e.to_string()),
-        parser_run_utc_epoch,
-        data_display_period,
-        overall_parsing_time.as_millis(),
-        total_days,
-        total_weeks,
-        total_months
-    );
+    let mut authors: Vec<AuthorAnalysis> = merged.into_values().collect();
+    authors.sort_by(|a, b| {
+        b.posts_analyzed
+            .cmp(&a.posts_analyzed)
+            .then_with(|| a.author.cmp(&b.author))
+    });
+    authors
+}
 
-    let final_output_struct = AnalysisOutput {
-        total_parsing_time_ms: overall_parsing_time.as_millis(),
-        overall_trade_data_span_days: total_days,
-        overall_trade_data_span_weeks: total_weeks,
-        overall_trade_data_span_months: total_months,
-        items: results,
-    };
+/// Parses the configured CSV and serializes the analysis in the requested format.
+pub fn run_trade_analysis(config: &RunConfig) -> Result<String, ParseError> {
+    let mut buffer = Vec::new();
+    run_trade_analysis_to(config, &mut buffer)?;
+    let output = String::from_utf8(buffer)
+        .map_err(|e| ParseError::Io(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+    Ok(output.strip_suffix('\n').unwrap_or(&output).to_string())
+}
 
-    if is_verbose {
-        println!("Serializing results to YAML format...");
+/// Like [`run_trade_analysis`], but writes to `out`. JSONL output is written
+/// line by line as each item is computed; other formats are written once complete.
+pub fn run_trade_analysis_to(config: &RunConfig, out: &mut impl Write) -> Result<(), ParseError> {
+    if config.is_verbose {
+        eprintln!("\n--- Starting Trade Analysis ---\n");
     }
-    let yaml_items_output = serde_yaml::to_string(&final_output_struct)?;
-    if is_verbose {
-        println!("YAML serialization complete.");
+    // A summary needs every item before it can pick the standouts, and split output
+    // and history CSVs are written once the analysis completes, so none of them stream.
+    if config.output_format == OutputFormat::Jsonl
+        && !config.summary
+        && config.split_output.is_none()
+        && config.history_csv.is_none()
+    {
+        analyze_trades_streaming(config, |event| {
+            let line = match event {
+                StreamEvent::Header(header) => jsonl::header_lines(header)?,
+                StreamEvent::Item(item) => jsonl::item_line(&item)?,
+            };
+            Ok(writeln!(out, "{}", line)?)
+        })?;
+    } else {
+        write_analysis_to(analyze_trades(config)?, config, out)?;
+    }
+    if config.is_verbose {
+        eprintln!("\n--- Trade Analysis Complete ---");
     }
+    Ok(())
+}
 
-    if is_verbose {
-        println!("\n--- Trade Analysis Complete ---");
+/// Writes a finished analysis the way [`run_trade_analysis_to`] does: the
+/// `history_csv` files first, then the `split_output` files or the rendered
+/// output on `out`. Split output writes files of its own rather than anything
+/// to `out`.
+pub fn write_analysis_to(
+    mut analysis: AnalysisOutput,
+    config: &RunConfig,
+    out: &mut impl Write,
+) -> Result<(), ParseError> {
+    if let Some(dir) = &config.history_csv {
+        let written = write_history_csv(&analysis, dir)?;
+        if config.is_verbose {
+            eprintln!("Wrote {} price history files to '{}'.", written, dir);
+        }
+        if !config.include_history {
+            for item in &mut analysis.items {
+                item.price_history = Vec::new();
+            }
+        }
+    }
+    if let Some(dir) = &config.split_output {
+        let written = write_split_output(&analysis, dir)?;
+        if config.is_verbose {
+            eprintln!(
+                "Wrote {} item files and manifest.yaml to '{}'.",
+                written, dir
+            );
+        }
+    } else {
+        writeln!(out, "{}", render_output(&analysis, config)?)?;
+    }
+    Ok(())
+}
+
+/// Everything gathered by the CSV pass, before per-item statistics are computed.
+#[derive(Default)]
+struct CollectedTrades {
+    item_data: HashMap<ItemName, ItemStats>,
+    author_data: HashMap<AuthorId, AuthorStats>,
+    /// Every in-window trade date, oldest first.
+    all_trade_dates: Vec<DateTime<FixedOffset>>,
+    out_of_range_records_count: u32,
+    skipped_records_count: u32,
+    observations_count: u32,
+    multi_item_posts_count: u32,
+    price_filtered_count: u32,
+    skipped_by_reason: BTreeMap<String, u32>,
+    duplicate_records_count: u32,
+    spam_filtered_count: u32,
+    author_filtered_count: u32,
+    input_files: Vec<InputFileSummary>,
+    parsing_time: std::time::Duration,
+}
+
+impl CollectedTrades {
+    fn count_skip(&mut self, reason: &SkipReason) {
+        *self
+            .skipped_by_reason
+            .entry(reason.key().to_string())
+            .or_default() += 1;
+    }
+}
+
+/// Parses the configured CSV and returns the structured analysis without serializing it.
+pub fn analyze_trades(config: &RunConfig) -> Result<AnalysisOutput, ParseError> {
+    TradeParser::new(config.clone()).finalize()
+}
+
+/// Numbers the items 1, 2, ... by supply plus demand posts, most first, without
+/// changing their order; equal counts are ranked by name.
+fn assign_popularity_ranks(items: &mut [ItemAnalysis]) {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| {
+        let posts =
+            |i: usize| items[i].supply_demand.supply_posts + items[i].supply_demand.demand_posts;
+        posts(b)
+            .cmp(&posts(a))
+            .then_with(|| items[a].item.cmp(&items[b].item))
+    });
+    for (rank, index) in order.into_iter().enumerate() {
+        items[index].popularity_rank = rank + 1;
+    }
+}
+
+/// Sum of each item's median price times its supply plus demand posts per week
+/// over `span_days`, the same rate `rough_selling_frequency` describes. Items
+/// without a median add nothing; `None` when the span is under a day.
+fn estimated_weekly_volume(items: &[ItemAnalysis], span_days: f64) -> Option<f64> {
+    if span_days < 1.0 {
+        return None;
+    }
+    let weekly_value: f64 = items
+        .iter()
+        .filter_map(|item| {
+            let median = item.estimated_price.median?;
+            let posts = item.supply_demand.supply_posts + item.supply_demand.demand_posts;
+            Some(f64::from(posts) / span_days * 7.0 * median)
+        })
+        .sum();
+    Some(round_cents(weekly_value))
+}
+
+/// Average of the items' median prices weighted by `weight`; items without a
+/// median are skipped, and `None` is returned when the weights sum to zero.
+fn weighted_market_index(
+    items: &[ItemAnalysis],
+    weight: impl Fn(&SupplyDemand) -> u32,
+) -> Option<f64> {
+    let (weighted_sum, total_weight) = items
+        .iter()
+        .filter_map(|item| {
+            let median = item.estimated_price.median?;
+            Some((median, f64::from(weight(&item.supply_demand))))
+        })
+        .fold((0.0, 0.0), |(sum, total), (median, w)| {
+            (sum + median * w, total + w)
+        });
+    (total_weight > 0.0).then(|| round_cents(weighted_sum / total_weight))
+}
+
+/// What [`analyze_trades_streaming`] hands to its callback, in this order.
+pub(crate) enum StreamEvent<'a> {
+    /// The finished metadata and authors, with `items` still empty.
+    Header(&'a AnalysisOutput),
+    /// One item, in output order, as soon as it is built.
+    Item(Box<ItemAnalysis>),
+}
+
+/// Runs the analysis without holding every item in memory, passing the header
+/// and then each item to `emit`. Returns the header.
+pub(crate) fn analyze_trades_streaming(
+    config: &RunConfig,
+    emit: impl FnMut(StreamEvent) -> Result<(), ParseError>,
+) -> Result<AnalysisOutput, ParseError> {
+    // Compiled up front so a bad pattern fails before the CSV pass.
+    let item_filter = item_name_filter(config)?;
+    let collected = collect_trades(config)?;
+    analyze_collected(collected, &item_filter, config, emit)
+}
+
+/// The shared back half of [`analyze_trades_streaming`] and [`TradeParser::finalize`]:
+/// turns the gathered trades into the header and items, passing each to `emit`.
+fn analyze_collected(
+    collected: CollectedTrades,
+    item_filter: &NameFilter,
+    config: &RunConfig,
+    mut emit: impl FnMut(StreamEvent) -> Result<(), ParseError>,
+) -> Result<AnalysisOutput, ParseError> {
+    let is_verbose = config.is_verbose;
+    let CollectedTrades {
+        item_data,
+        author_data,
+        all_trade_dates,
+        out_of_range_records_count,
+        skipped_records_count,
+        observations_count,
+        multi_item_posts_count,
+        price_filtered_count,
+        skipped_by_reason,
+        duplicate_records_count,
+        spam_filtered_count,
+        author_filtered_count,
+        input_files,
+        parsing_time: overall_parsing_time,
+    } = collected;
+
+    let earliest_message_utc_epoch = all_trade_dates.first().map(|dt| dt.timestamp());
+    let latest_message_utc_epoch = all_trade_dates.last().map(|dt| dt.timestamp());
+    let run_time = Utc::now();
+    let parser_run_utc_epoch = run_time.timestamp();
+
+    let total_duration = trade_data_span(&all_trade_dates);
+    let total_days = total_duration.num_days() as f64;
+    let total_weeks = total_duration.num_weeks() as f64;
+    let total_months = total_days / 30.44;
+    let data_display_period = data_period_label(&all_trade_dates);
+
+    if is_verbose {
+        eprintln!("\nAggregating and sorting item data...");
+    }
+    let global_market_saturation = saturation(
+        item_data.values().map(|stats| stats.supply_posts).sum(),
+        item_data.values().map(|stats| stats.demand_posts).sum(),
+    );
+    let context = DatasetContext::new(&item_data, &all_trade_dates, run_time);
+    let item_data_volume: f64 = item_data.values().flat_map(|stats| &stats.prices).sum();
+    let weekday_counts = weekday_counts(&all_trade_dates);
+    let weekend_to_weekday_ratio = weekend_to_weekday_ratio(&weekday_counts);
+    let hourly_activity = hourly_counts(&all_trade_dates);
+    let peak_hour_utc = peak_hour(&hourly_activity);
+    let weekly_pattern = WEEKDAYS
+        .iter()
+        .zip(weekday_counts)
+        .map(|(day, count)| (weekday_name(*day).to_string(), count))
+        .collect();
+    // Which items make the output depends only on their raw stats, so the counts
+    // for the metadata are known before any item is analyzed.
+    let mut kept_items: Vec<(ItemName, ItemStats)> = Vec::new();
+    let mut items_filtered = 0;
+    let mut items_suppressed_low_data = 0;
+    let mut items_below_min_confidence = 0;
+    let mut items_omitted = 0;
+    for (item_name, stats) in sort_item_stats(item_data, config.sort_key(), config.sort_ascending())
+    {
+        if !item_filter.allows(item_name.as_str()) {
+            items_filtered += 1;
+            continue;
+        }
+        if is_suppressed(&item_name, &stats, config) {
+            items_suppressed_low_data += 1;
+            continue;
+        }
+        if is_below_min_confidence(&item_name, &stats, config) {
+            items_below_min_confidence += 1;
+            continue;
+        }
+        if config.top.is_some_and(|top| kept_items.len() >= top) {
+            items_omitted += 1;
+            continue;
+        }
+        kept_items.push((item_name, stats));
+    }
+
+    let mut authors: Vec<AuthorAnalysis> = author_data
+        .into_iter()
+        .map(|(author_id, stats)| AuthorAnalysis {
+            author_id: author_id.0,
+            author: stats.author,
+            posts_analyzed: stats.posts_analyzed,
+            items_traded: stats
+                .items_traded
+                .into_iter()
+                .map(ItemName::into_inner)
+                .collect(),
+            avg_price_listed: if stats.price_count > 0 {
+                Some(stats.price_sum / stats.price_count as f64)
+            } else {
+                None
+            },
+            total_value_listed: round_cents(stats.price_sum),
+            supply_posts: stats.supply_posts,
+            demand_posts: stats.demand_posts,
+        })
+        .collect();
+    authors.sort_by(|a, b| {
+        b.posts_analyzed
+            .cmp(&a.posts_analyzed)
+            .then_with(|| a.author.cmp(&b.author))
+    });
+
+    let metadata = AnalysisMetadata {
+        earliest_message_utc_epoch,
+        latest_message_utc_epoch,
+        parser_run_utc_epoch,
+        csv_data_time_period: data_display_period,
+        date_from: config.date_from.map(|d| d.to_string()),
+        date_to: config.date_to.map(|d| d.to_string()),
+        out_of_range_records_count,
+        input_files,
+        skipped_records_count,
+        observations_count,
+        multi_item_posts_count,
+        price_filtered_count,
+        skipped_by_reason,
+        duplicate_records_count,
+        spam_filtered_count,
+        author_filtered_count,
+        items_filtered,
+        items_suppressed_low_data,
+        items_below_min_confidence,
+        items_omitted,
+        global_market_saturation,
+    };
+
+    let header = AnalysisOutput {
+        total_parsing_time_ms: overall_parsing_time.as_millis(),
+        overall_trade_data_span_days: total_days,
+        overall_trade_data_span_weeks: total_weeks,
+        overall_trade_data_span_months: total_months,
+        global_volume: round_cents(item_data_volume),
+        currency: config.currency.clone(),
+        market_index: None,
+        index_by_supply_only: None,
+        index_by_demand_only: None,
+        total_items_in_dataset: 0,
+        estimated_weekly_volume_usd: None,
+        weekly_pattern,
+        weekend_to_weekday_ratio,
+        hourly_activity,
+        peak_hour_utc,
+        items: Vec::new(),
+        authors,
+        metadata,
+    };
+    emit(StreamEvent::Header(&header))?;
+
+    for (item_name, stats) in kept_items {
+        emit(StreamEvent::Item(Box::new(analyze_item(
+            item_name, stats, config, &context,
+        ))))?;
+    }
+    if is_verbose {
+        eprintln!("Item data aggregation complete.");
+    }
+
+    Ok(header)
+}
+
+/// Yields one [`ItemAnalysis`] at a time instead of building the whole list, or
+/// takes [`TradeRecord`]s one by one through [`feed_record`](Self::feed_record)
+/// and computes the whole [`AnalysisOutput`] with [`finalize`](Self::finalize).
+///
+/// As an iterator, the CSV pass runs on the first call to `next`; after that
+/// each item's statistics are computed only when it is requested, in the same
+/// order as [`AnalysisOutput::items`]. A failed CSV pass is yielded once as an
+/// `Err`, and the iterator ends after it.
+///
+/// ```no_run
+/// use goskateapiparser::{RunConfig, TradeParser};
+///
+/// let config = RunConfig {
+///     file_paths: vec!["src/data/tradexport_1755362248.csv".to_string()],
+///     ..RunConfig::default()
+/// };
+/// for item in TradeParser::new(config) {
+///     let item = item?;
+///     println!("{}: {:?}", item.item, item.estimated_price.median);
+/// }
+/// # Ok::<(), goskateapiparser::ParseError>(())
+/// ```
+///
+/// Once a record has been fed, the configured input files are no longer read:
+/// the analysis, or the iterator, covers only the fed records.
+///
+/// ```
+/// use goskateapiparser::{AuthorId, TradeParser, TradeRecord};
+///
+/// let mut parser = TradeParser::default();
+/// parser.feed_record(TradeRecord {
+///     author_id: AuthorId(1),
+///     author: "rider".to_string(),
+///     date: "2025-08-01T12:00:00+00:00".to_string(),
+///     content: Some("WTS Hot Spring 500".to_string()),
+///     attachments: None,
+///     reactions: None,
+/// })?;
+/// let analysis = parser.finalize()?;
+/// assert_eq!(analysis.items[0].estimated_price.median, Some(500.0));
+/// # Ok::<(), goskateapiparser::ParseError>(())
+/// ```
+pub struct TradeParser {
+    config: RunConfig,
+    state: StreamState,
+}
+
+enum StreamState {
+    NotStarted,
+    /// Records handed to `feed_record` so far, already classified and merged.
+    Feeding(Box<TradeCollector>),
+    Streaming {
+        pending: VecDeque<(ItemName, ItemStats)>,
+        context: DatasetContext,
+        item_filter: NameFilter,
+        /// Items returned so far, checked against `--top`.
+        yielded: usize,
+    },
+    Done,
+}
+
+impl Default for TradeParser {
+    /// A parser with [`RunConfig::default`], meant to be fed records directly.
+    fn default() -> Self {
+        TradeParser::new(RunConfig::default())
+    }
+}
+
+impl TradeParser {
+    pub fn new(config: RunConfig) -> Self {
+        TradeParser {
+            config,
+            state: StreamState::NotStarted,
+        }
+    }
+
+    /// Classifies `record` and merges it into the running statistics, applying the
+    /// same duplicate, author, date, price and spam filters as the CSV pass. Fails
+    /// on an invalid configuration, on an unparseable date with `strict`, or once
+    /// iteration has started.
+    pub fn feed_record(&mut self, record: TradeRecord) -> Result<(), ParseError> {
+        feeding_collector(&mut self.state, &self.config, "feed records to")?
+            .feed(record, &self.config)
+    }
+
+    /// The per-item statistics gathered from the records fed so far, for
+    /// checkpointing a pipeline with [`ItemStats::snapshot`]. Empty before the
+    /// first record is fed or restored, and once iteration has started.
+    pub fn item_stats(&self) -> &HashMap<ItemName, ItemStats> {
+        static NO_ITEM_STATS: LazyLock<HashMap<ItemName, ItemStats>> = LazyLock::new(HashMap::new);
+        match &self.state {
+            StreamState::Feeding(collector) => &collector.trades.item_data,
+            _ => &NO_ITEM_STATS,
+        }
+    }
+
+    /// Seeds the parser with statistics from an earlier run, such as ones read back
+    /// with [`ItemStats::restore`], merged into anything fed so far, so that
+    /// [`feed_record`](Self::feed_record) carries on where that run stopped. Only the
+    /// item statistics carry over: duplicate and spam detection, authors and the
+    /// record counts start afresh, and the data span covers the restored prices'
+    /// dates plus the records fed here. Fails once iteration has started.
+    pub fn restore_item_stats(
+        &mut self,
+        stats: impl IntoIterator<Item = (ItemName, ItemStats)>,
+    ) -> Result<(), ParseError> {
+        let trades =
+            &mut feeding_collector(&mut self.state, &self.config, "restore item stats into")?
+                .trades;
+        for (name, item_stats) in stats {
+            trades
+                .all_trade_dates
+                .extend(item_stats.trade_dates.iter().copied());
+            trades.item_data.entry(name).or_default().merge(item_stats);
+        }
+        Ok(())
+    }
+
+    /// Computes the full analysis, market index and popularity ranks included, from
+    /// the fed records, or from the configured input files when none were fed.
+    pub fn finalize(self) -> Result<AnalysisOutput, ParseError> {
+        let item_filter = item_name_filter(&self.config)?;
+        let collected = match self.state {
+            StreamState::NotStarted => collect_trades(&self.config)?,
+            StreamState::Feeding(collector) => collector.finish(&self.config),
+            StreamState::Streaming { .. } | StreamState::Done => {
+                return Err(iteration_started_error("finalize"));
+            }
+        };
+        analyze_all_items(collected, &item_filter, &self.config)
+    }
+}
+
+/// Runs [`analyze_collected`] to completion, then fills in the figures that need
+/// every item: the market indexes, weekly volume and popularity ranks.
+fn analyze_all_items(
+    collected: CollectedTrades,
+    item_filter: &NameFilter,
+    config: &RunConfig,
+) -> Result<AnalysisOutput, ParseError> {
+    let mut items = Vec::new();
+    let mut analysis = analyze_collected(collected, item_filter, config, |event| {
+        if let StreamEvent::Item(item) = event {
+            items.push(*item);
+        }
+        Ok(())
+    })?;
+    // The index and ranks need every item, which the streamed header can't wait for.
+    analysis.market_index =
+        weighted_market_index(&items, |counts| counts.supply_posts + counts.demand_posts);
+    analysis.index_by_supply_only = weighted_market_index(&items, |counts| counts.supply_posts);
+    analysis.index_by_demand_only = weighted_market_index(&items, |counts| counts.demand_posts);
+    analysis.estimated_weekly_volume_usd =
+        estimated_weekly_volume(&items, analysis.overall_trade_data_span_days);
+    assign_popularity_ranks(&mut items);
+    analysis.total_items_in_dataset = items.len();
+    analysis.items = items;
+    Ok(analysis)
+}
+
+/// [`analyze_trades`] over inputs already read into memory, each with the path it
+/// came from; the async API does its file I/O on the runtime and hands the bytes here.
+#[cfg(feature = "async")]
+pub(crate) fn analyze_loaded_inputs(
+    config: &RunConfig,
+    inputs: Vec<(String, Vec<u8>)>,
+) -> Result<AnalysisOutput, ParseError> {
+    let item_filter = item_name_filter(config)?;
+    let input_paths: Vec<String> = inputs.iter().map(|(path, _)| path.clone()).collect();
+    let mut collector = TradeCollector::new(config, &input_paths)?;
+    for (path, bytes) in inputs {
+        collector.read_source(&path, bytes.as_slice(), config)?;
+    }
+    collector.classify_pending(config)?;
+    analyze_all_items(collector.finish(config), &item_filter, config)
+}
+
+/// The collector behind [`TradeParser::feed_record`], created on first use;
+/// `action` names the call for the error once iteration has started.
+fn feeding_collector<'a>(
+    state: &'a mut StreamState,
+    config: &RunConfig,
+    action: &str,
+) -> Result<&'a mut TradeCollector, ParseError> {
+    if let StreamState::NotStarted = state {
+        // Checked now rather than in `finalize`, which would otherwise be the
+        // first to notice a bad item pattern.
+        item_name_filter(config)?;
+        *state = StreamState::Feeding(Box::new(TradeCollector::for_feeding(config)?));
+    }
+    match state {
+        StreamState::Feeding(collector) => Ok(collector),
+        _ => Err(iteration_started_error(action)),
+    }
+}
+
+fn iteration_started_error(action: &str) -> ParseError {
+    ParseError::InvalidArgument(format!(
+        "Cannot {} a TradeParser after iterating over it",
+        action
+    ))
+}
+
+impl Iterator for TradeParser {
+    type Item = Result<ItemAnalysis, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if matches!(
+            self.state,
+            StreamState::NotStarted | StreamState::Feeding(_)
+        ) {
+            let started = match std::mem::replace(&mut self.state, StreamState::Done) {
+                StreamState::Feeding(collector) => item_name_filter(&self.config)
+                    .map(|filter| (filter, collector.finish(&self.config))),
+                _ => item_name_filter(&self.config).and_then(|filter| {
+                    collect_trades(&self.config).map(|collected| (filter, collected))
+                }),
+            };
+            match started {
+                Ok((item_filter, collected)) => {
+                    let context = DatasetContext::new(
+                        &collected.item_data,
+                        &collected.all_trade_dates,
+                        Utc::now(),
+                    );
+                    self.state = StreamState::Streaming {
+                        context,
+                        item_filter,
+                        pending: sort_item_stats(
+                            collected.item_data,
+                            self.config.sort_key(),
+                            self.config.sort_ascending(),
+                        )
+                        .into(),
+                        yielded: 0,
+                    };
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        let StreamState::Streaming {
+            pending,
+            context,
+            item_filter,
+            yielded,
+        } = &mut self.state
+        else {
+            return None;
+        };
+        while let Some((item_name, stats)) = pending.pop_front() {
+            if self.config.top.is_some_and(|top| *yielded >= top) {
+                break;
+            }
+            if !item_filter.allows(item_name.as_str())
+                || is_suppressed(&item_name, &stats, &self.config)
+                || is_below_min_confidence(&item_name, &stats, &self.config)
+            {
+                continue;
+            }
+            *yielded += 1;
+            return Some(Ok(analyze_item(item_name, stats, &self.config, context)));
+        }
+        self.state = StreamState::Done;
+        None
+    }
+}
+
+/// Reads, deduplicates and classifies every configured input, merging the
+/// observations into per-item and per-author statistics.
+fn collect_trades(config: &RunConfig) -> Result<CollectedTrades, ParseError> {
+    let input_paths = resolve_input_paths(config)?;
+    let mut collector = TradeCollector::new(config, &input_paths)?;
+    collector.read_inputs(&input_paths, config)?;
+    collector.classify_pending(config)?;
+    Ok(collector.finish(config))
+}
+
+/// What the fed records are called in log messages and the input file summary.
+const FED_RECORDS_SOURCE: &str = "fed records";
+
+/// The record-by-record half of the analysis, shared by the CSV pass and
+/// [`TradeParser::feed_record`]: records are queued after the duplicate and
+/// author checks, classified, then merged in queue order.
+struct TradeCollector {
+    matchers: Matchers,
+    start_time: Instant,
+    /// What each `PendingRecord::source` refers to, for log and error messages.
+    sources: Vec<String>,
+    pending: Vec<PendingRecord>,
+    seen_records: HashSet<u64>,
+    spam_filter: SpamFilter,
+    progress: ProgressBar,
+    processed_records_count: u32,
+    trades: CollectedTrades,
+}
+
+impl TradeCollector {
+    fn new(config: &RunConfig, input_paths: &[String]) -> Result<Self, ParseError> {
+        let start_time = Instant::now();
+        if config.is_verbose {
+            eprintln!("Loading item keywords...");
+        }
+        let matchers = Matchers::new(config, input_paths)?;
+        if config.is_verbose {
+            eprintln!("Item keywords loaded successfully.");
+        }
+        Ok(TradeCollector {
+            matchers,
+            start_time,
+            sources: Vec::new(),
+            pending: Vec::new(),
+            seen_records: HashSet::new(),
+            spam_filter: SpamFilter::new(config.spam_window_secs),
+            progress: ProgressBar::hidden(),
+            processed_records_count: 0,
+            trades: CollectedTrades::default(),
+        })
+    }
+
+    /// A collector with no input files, whose records arrive through [`Self::feed`].
+    fn for_feeding(config: &RunConfig) -> Result<Self, ParseError> {
+        let mut collector = TradeCollector::new(config, &[])?;
+        collector.sources.push(FED_RECORDS_SOURCE.to_string());
+        collector.trades.input_files.push(InputFileSummary {
+            path: FED_RECORDS_SOURCE.to_string(),
+            records: 0,
+        });
+        Ok(collector)
+    }
+
+    /// Deserializes every input in turn, queueing its records for [`Self::classify_pending`].
+    fn read_inputs(
+        &mut self,
+        input_paths: &[String],
+        config: &RunConfig,
+    ) -> Result<(), ParseError> {
+        if config.is_verbose {
+            eprintln!("Starting to deserialize CSV records...");
+        }
+        if !config.is_quiet {
+            self.progress = new_progress_bar();
+        }
+        for file_path in input_paths {
+            self.read_source(file_path, open_source(file_path, config)?, config)?;
+        }
+        Ok(())
+    }
+
+    /// Deserializes one input, already opened as `reader`, queueing its records.
+    fn read_source(
+        &mut self,
+        file_path: &str,
+        reader: impl Read,
+        config: &RunConfig,
+    ) -> Result<(), ParseError> {
+        let source = self.sources.len();
+        self.sources.push(file_path.to_string());
+        let mut rdr = ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(BufReader::new(reader));
+        let mut file_records_count = 0;
+
+        for (i, result) in rdr.deserialize().enumerate() {
+            let record: TradeRecord = match result {
+                Ok(r) => r,
+                Err(e) if config.strict => {
+                    self.progress.finish_and_clear();
+                    return Err(ParseError::CsvError(e));
+                }
+                Err(e) => {
+                    self.progress.suspend(|| {
+                        if !config.is_quiet {
+                            eprintln!(
+                                "WARNING: Skipping malformed record on line {} of '{}': {}",
+                                i + 2,
+                                file_path,
+                                e
+                            )
+                        }
+                    });
+                    self.trades.skipped_records_count += 1;
+                    self.trades.count_skip(&SkipReason::Malformed);
+                    continue;
+                }
+            };
+            file_records_count += 1;
+            // Redrawing on every record would dominate the runtime of small rows.
+            if file_records_count % PROGRESS_TICK_INTERVAL == 0 {
+                let duplicates = self.trades.duplicate_records_count;
+                self.progress
+                    .set_position(u64::from(self.processed_records_count + duplicates));
+                self.progress.set_message(format!(
+                    "{} skipped",
+                    self.trades.skipped_records_count + duplicates
+                ));
+            }
+            self.queue(record, source, i + 2, config);
+        }
+
+        self.trades.input_files.push(InputFileSummary {
+            path: display_path(file_path).to_string(),
+            records: file_records_count,
+        });
+        Ok(())
+    }
+
+    /// Classifies and merges one record straight away; see [`TradeParser::feed_record`].
+    fn feed(&mut self, record: TradeRecord, config: &RunConfig) -> Result<(), ParseError> {
+        let summary = &mut self.trades.input_files[0];
+        summary.records += 1;
+        let line = summary.records as usize;
+        self.queue(record, 0, line, config);
+        match self.pending.pop() {
+            Some(pending) => {
+                let outcome = classify_record(&pending.record, &self.matchers, config);
+                self.absorb(&pending, outcome, config)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Queues `record` for classification unless it repeats an earlier record or
+    /// its author is filtered out.
+    fn queue(&mut self, record: TradeRecord, source: usize, line: usize, config: &RunConfig) {
+        // The same message can appear in several overlapping exports.
+        let skip_reason = if !self.seen_records.insert(record_hash(&record)) {
+            self.trades.duplicate_records_count += 1;
+            Some(SkipReason::Duplicate)
+        } else if !self.matchers.authors.allows(&record.author) {
+            self.trades.author_filtered_count += 1;
+            Some(SkipReason::AuthorFiltered)
+        } else {
+            None
+        };
+        if let Some(reason) = skip_reason {
+            if config.is_verbose {
+                eprintln!(
+                    "Skipping record {} in '{}' (Author: {}): {}.",
+                    line,
+                    self.sources[source],
+                    record.author,
+                    reason.describe()
+                );
+            }
+            self.trades.count_skip(&reason);
+            return;
+        }
+        self.processed_records_count += 1;
+        self.pending.push(PendingRecord {
+            source,
+            line,
+            record,
+        });
+    }
+
+    /// Classifies every queued record in parallel; the results come back in queue
+    /// order and are merged sequentially.
+    fn classify_pending(&mut self, config: &RunConfig) -> Result<(), ParseError> {
+        let pending_records = std::mem::take(&mut self.pending);
+        if config.is_verbose {
+            eprintln!(
+                "Classifying {} records in parallel...",
+                pending_records.len()
+            );
+        }
+        let matchers = &self.matchers;
+        let classify = || -> Vec<RecordOutcome> {
+            pending_records
+                .par_iter()
+                .map(|pending| classify_record(&pending.record, matchers, config))
+                .collect()
+        };
+        let outcomes = match config.jobs {
+            Some(jobs) => ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build()
+                .map_err(|e| ParseError::InvalidArgument(format!("--jobs {}: {}", jobs, e)))?
+                .install(classify),
+            None => classify(),
+        };
+        for (pending, outcome) in pending_records.iter().zip(outcomes) {
+            self.absorb(pending, outcome, config)?;
+        }
+        Ok(())
+    }
+
+    /// Merges one classified record into the per-item and per-author statistics,
+    /// after aliases and the price and spam filters.
+    fn absorb(
+        &mut self,
+        pending: &PendingRecord,
+        outcome: RecordOutcome,
+        config: &RunConfig,
+    ) -> Result<(), ParseError> {
+        let is_verbose = config.is_verbose;
+        let record = &pending.record;
+        let trades = &mut self.trades;
+        let observations = match outcome {
+            RecordOutcome::Observed(observations) => observations,
+            RecordOutcome::Skipped { reason, trade_date } => {
+                if config.strict
+                    && let SkipReason::UnparseableRfc3339(date) | SkipReason::UnparseableDate(date) =
+                        &reason
+                {
+                    return Err(ParseError::InvalidDate(format!(
+                        "'{}' on line {} of '{}'",
+                        date, pending.line, self.sources[pending.source]
+                    )));
+                }
+                // Records inside the date window still count toward the data span.
+                trades.all_trade_dates.extend(trade_date);
+                if is_verbose {
+                    let verb = if matches!(reason, SkipReason::DateRangeFiltered(_)) {
+                        "Ignoring"
+                    } else {
+                        "Skipping"
+                    };
+                    let location = if self.sources.len() > 1 {
+                        format!(" in '{}'", self.sources[pending.source])
+                    } else {
+                        String::new()
+                    };
+                    eprintln!(
+                        "{} record {}{} (Author: {}): {}.",
+                        verb,
+                        pending.line,
+                        location,
+                        record.author,
+                        reason.describe()
+                    );
+                }
+                match reason {
+                    SkipReason::DateRangeFiltered(_) => trades.out_of_range_records_count += 1,
+                    _ => trades.skipped_records_count += 1,
+                }
+                trades.count_skip(&reason);
+                return Ok(());
+            }
+        };
+        // Fold variant names into one canonical item before any stats are keyed by it,
+        // keeping the first mention of each, so two aliases of one item observe it once.
+        let mut seen_items = HashSet::new();
+        let observations: Vec<_> = observations
+            .into_iter()
+            .filter_map(|mut observation| {
+                if let Some(canonical) = config.item_aliases.get(observation.item_name.as_str()) {
+                    if is_verbose {
+                        eprintln!(
+                            "Record {} (Author: {}): resolved alias '{}' to '{}'.",
+                            pending.line, record.author, observation.item_name, canonical
+                        );
+                    }
+                    observation.item_name = ItemName::from(canonical.as_str());
+                }
+                seen_items
+                    .insert(observation.item_name.clone())
+                    .then_some(observation)
+            })
+            .collect();
+        let mut kept_observations = 0;
+        for mut observation in observations {
+            let listed_prices = observation.prices.len();
+            observation
+                .prices
+                .retain(|&price| (config.min_price..=config.max_price).contains(&f64::from(price)));
+            trades.price_filtered_count += (listed_prices - observation.prices.len()) as u32;
+            if observation.prices.is_empty() {
+                if is_verbose {
+                    eprintln!(
+                        "Skipping record {} (Author: {}): Every '{}' price is outside {} to {}.",
+                        pending.line,
+                        record.author,
+                        observation.item_name,
+                        config.min_price,
+                        config.max_price
+                    );
+                }
+                trades.count_skip(&SkipReason::PriceFiltered);
+                continue;
+            }
+
+            if config.spam_window_secs > 0
+                && self.spam_filter.check_and_record(
+                    record.author_id,
+                    &observation.item_name,
+                    observation.prices[0],
+                    observation.trade_date,
+                )
+            {
+                if is_verbose {
+                    eprintln!(
+                        "Skipping record {} (Author: {}): Repeat of their '{}' post within {} seconds.",
+                        pending.line, record.author, observation.item_name, config.spam_window_secs
+                    );
+                }
+                trades.spam_filtered_count += 1;
+                trades.count_skip(&SkipReason::SpamDetected);
+                continue;
+            }
+            trades.all_trade_dates.push(observation.trade_date);
+            trades.observations_count += 1;
+            kept_observations += 1;
+
+            if config.include_authors {
+                let author_stats =
+                    trades
+                        .author_data
+                        .entry(record.author_id)
+                        .or_insert_with(|| AuthorStats {
+                            author: record.author.clone(),
+                            ..AuthorStats::default()
+                        });
+                author_stats.posts_analyzed += 1;
+                author_stats
+                    .items_traded
+                    .insert(observation.item_name.clone());
+                author_stats.price_sum +=
+                    f64::from(observation.prices.iter().copied().sum::<Price>());
+                author_stats.price_count += observation.prices.len() as u32;
+                match observation.post_kind {
+                    PostKind::Supply => author_stats.supply_posts += 1,
+                    PostKind::Demand => author_stats.demand_posts += 1,
+                    _ => {}
+                }
+            }
+
+            let stats = trades.item_data.entry(observation.item_name).or_default();
+            for price_val in observation.prices.into_iter().map(f64::from) {
+                stats.prices.push(price_val);
+                stats
+                    .unit_prices
+                    .push(price_val / f64::from(observation.quantity));
+                stats.trade_dates.push(observation.trade_date);
+                stats.post_kinds.push(observation.post_kind);
+                stats.conditions.push(observation.condition);
+                stats.authors.push(record.author.clone());
+                match observation.post_kind {
+                    PostKind::Supply => stats.supply_prices.push(price_val),
+                    PostKind::Demand => stats.demand_prices.push(price_val),
+                    _ => {}
+                }
+            }
+            if observation.is_range {
+                stats.range_derived_posts += 1;
+            }
+            *stats
+                .condition_breakdown
+                .entry(observation.condition.as_str().to_string())
+                .or_default() += 1;
+            stats.flexible_posts += u32::from(observation.is_flexible);
+            stats.firm_posts += u32::from(observation.is_firm);
+            // Each reaction counts as one more interested trader alongside the poster.
+            let weight = if config.weight_reactions {
+                1 + record.reactions.as_deref().map_or(0, parse_reaction_count)
+            } else {
+                1
+            };
+            match observation.post_kind {
+                PostKind::Completed => stats.completed_posts += 1,
+                PostKind::Supply => stats.supply_posts += weight,
+                PostKind::Demand => stats.demand_posts += weight,
+                PostKind::Swap => stats.swap_posts += 1,
+                PostKind::Untagged => {}
+            }
+        }
+        // Only posts still giving several items after the filters count as multi-item.
+        if kept_observations > 1 {
+            trades.multi_item_posts_count += 1;
+        }
+        Ok(())
+    }
+
+    /// Closes the progress bar, sorts the trade dates and applies `--reject-outliers`.
+    fn finish(self, config: &RunConfig) -> CollectedTrades {
+        let is_verbose = config.is_verbose;
+        let mut trades = self.trades;
+        // Finish before returning so the bar never interleaves with the rendered output.
+        self.progress.set_position(u64::from(
+            self.processed_records_count + trades.duplicate_records_count,
+        ));
+        self.progress.finish_with_message(format!(
+            "{} skipped",
+            trades.skipped_records_count
+                + trades.out_of_range_records_count
+                + trades.duplicate_records_count
+                + trades.spam_filtered_count
+                + trades.author_filtered_count
+        ));
+        if is_verbose {
+            eprintln!(
+                "Finished processing {} records ({} skipped, {} outside date window, {} duplicates, {} spam, {} filtered by author).",
+                self.processed_records_count,
+                trades.skipped_records_count,
+                trades.out_of_range_records_count,
+                trades.duplicate_records_count,
+                trades.spam_filtered_count,
+                trades.author_filtered_count
+            );
+            for (reason, count) in &trades.skipped_by_reason {
+                eprintln!("  Skipped ({}): {}", reason, count);
+            }
+        }
+
+        trades.parsing_time = self.start_time.elapsed();
+        trades.all_trade_dates.sort();
+
+        if trades.all_trade_dates.is_empty() && !config.is_quiet {
+            eprintln!(
+                "\nWARNING: No valid trade data found after parsing. Output will contain no item analysis."
+            );
+        }
+
+        if config.reject_outliers {
+            if is_verbose {
+                eprintln!("\nRejecting price outliers (1.5 x IQR)...");
+            }
+            for (item_name, stats) in trades.item_data.iter_mut() {
+                let before = stats.prices.len();
+                stats.reject_outliers();
+                if is_verbose {
+                    eprintln!(
+                        "  {}: {} prices before, {} after outlier rejection.",
+                        item_name,
+                        before,
+                        stats.prices.len()
+                    );
+                }
+            }
+        }
+        trades
+    }
+}
+
+/// Pairs every price with its date, direction and author, sorted by date ascending.
+/// Must run before `stats.prices` is sorted, while the vectors are still aligned.
+fn price_history(stats: &ItemStats) -> Vec<PricePoint> {
+    let mut points: Vec<(DateTime<FixedOffset>, PricePoint)> = (0..stats.prices.len())
+        .map(|i| {
+            let price = stats.prices[i];
+            let quantity = (price / stats.unit_prices[i]).round() as u32;
+            let condition = stats.conditions[i];
+            let point = PricePoint {
+                date: stats.trade_dates[i].to_rfc3339(),
+                price,
+                direction: match stats.post_kinds[i] {
+                    PostKind::Supply | PostKind::Completed => "sell",
+                    PostKind::Demand => "buy",
+                    PostKind::Swap => "swap",
+                    PostKind::Untagged => "untagged",
+                }
+                .to_string(),
+                quantity: (quantity > 1).then_some(quantity),
+                condition: (condition != Condition::Unknown)
+                    .then(|| condition.as_str().to_string()),
+                author: stats.authors[i].clone(),
+            };
+            (stats.trade_dates[i], point)
+        })
+        .collect();
+    // Stable, so posts sharing a timestamp keep their record order.
+    points.sort_by_key(|(date, _)| *date);
+    points.into_iter().map(|(_, point)| point).collect()
+}
+
+/// The `csv_data_time_period` shown in the metadata, such as `"2 weeks, 3 days"`.
+fn data_period_label(sorted_dates: &[DateTime<FixedOffset>]) -> String {
+    let total_duration = trade_data_span(sorted_dates);
+    let total_days = total_duration.num_days();
+    if sorted_dates.is_empty() {
+        "No data available".to_string()
+    } else if total_duration.num_seconds() == 0 {
+        "Less than a day (or only one record)".to_string()
+    } else if total_days as f64 / 30.44 >= 1.0 {
+        format!("{} months, {} days", total_days / 30, total_days % 30)
+    } else if total_duration.num_weeks() >= 1 {
+        format!("{} weeks, {} days", total_days / 7, total_days % 7)
+    } else {
+        format!("{:.0} days", total_days as f64)
+    }
+}
+
+/// Time between the first and last trade in `sorted_dates`.
+fn trade_data_span(sorted_dates: &[DateTime<FixedOffset>]) -> Duration {
+    match (sorted_dates.first(), sorted_dates.last()) {
+        (Some(first), Some(last)) => last.signed_duration_since(*first),
+        _ => Duration::zero(),
+    }
+}
+
+/// Orders items by `sort_key`, ascending or descending.
+fn sort_item_stats(
+    item_data: HashMap<ItemName, ItemStats>,
+    sort_key: SortKey,
+    ascending: bool,
+) -> Vec<(ItemName, ItemStats)> {
+    let mut sorted_item_data: Vec<(ItemName, ItemStats)> = item_data.into_iter().collect();
+    sorted_item_data.sort_by(comparator_for(sort_key, ascending));
+    sorted_item_data
+}
+
+/// Comparator for `sort_key`. Numeric ties fall back to the median price, and
+/// `ascending` reverses the whole ordering, tie-break included.
+fn comparator_for(
+    key: SortKey,
+    ascending: bool,
+) -> impl Fn(&(ItemName, ItemStats), &(ItemName, ItemStats)) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    let cmp_f64 = |a: f64, b: f64| a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+    move |(name_a, a), (name_b, b)| {
+        let by_median = || cmp_f64(median_for_sort(&a.prices), median_for_sort(&b.prices));
+        let ordering = match key {
+            SortKey::Price => by_median(),
+            SortKey::Volume => (a.supply_posts + a.demand_posts)
+                .cmp(&(b.supply_posts + b.demand_posts))
+                .then_with(by_median),
+            SortKey::Frequency => cmp_f64(posts_per_day(a), posts_per_day(b)).then_with(by_median),
+            SortKey::Liquidity => cmp_f64(raw_liquidity(a), raw_liquidity(b)).then_with(by_median),
+            SortKey::Name => name_a.cmp(name_b),
+            SortKey::Volatility => {
+                cmp_f64(std_dev_for_sort(&a.prices), std_dev_for_sort(&b.prices))
+                    .then_with(by_median)
+            }
+        };
+        if ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
+}
+
+/// Population standard deviation, with fewer than two prices sorting as 0.
+fn std_dev_for_sort(prices: &[f64]) -> f64 {
+    population_variance(prices).map_or(0.0, f64::sqrt)
+}
+
+/// Arithmetic mean of `prices`; `None` when empty.
+fn mean_price(prices: &[f64]) -> Option<f64> {
+    if prices.is_empty() {
+        return None;
+    }
+    Some(prices.iter().sum::<f64>() / prices.len() as f64)
+}
+
+/// Population variance of `prices`; `None` for fewer than two prices.
+///
+/// Deviations are taken from the first price before averaging, so identical prices
+/// give exactly 0 instead of the rounding error of an inexact mean.
+fn population_variance(prices: &[f64]) -> Option<f64> {
+    let (&first, _) = prices.split_first().filter(|(_, rest)| !rest.is_empty())?;
+    let count = prices.len() as f64;
+    let mean_offset = prices.iter().map(|p| p - first).sum::<f64>() / count;
+    Some(
+        prices
+            .iter()
+            .map(|p| (p - first - mean_offset).powi(2))
+            .sum::<f64>()
+            / count,
+    )
+}
+
+/// Median of unsorted `prices`, with items lacking prices sorting as 0.
+fn median_for_sort(prices: &[f64]) -> f64 {
+    median_of_unsorted(prices).unwrap_or(0.0)
+}
+
+/// Unnormalized liquidity: supply + demand posts times [`posts_per_day`].
+fn raw_liquidity(stats: &ItemStats) -> f64 {
+    (stats.supply_posts + stats.demand_posts) as f64 * posts_per_day(stats)
+}
+
+/// Supply + demand posts per day between the item's first and last trade,
+/// counting anything shorter than a day as one day.
+fn posts_per_day(stats: &ItemStats) -> f64 {
+    let posts = (stats.supply_posts + stats.demand_posts) as f64;
+    let (Some(first), Some(last)) = (
+        stats.trade_dates.iter().min(),
+        stats.trade_dates.iter().max(),
+    ) else {
+        return 0.0;
+    };
+    let days = last.signed_duration_since(*first).num_seconds() as f64 / 86_400.0;
+    posts / days.max(1.0)
+}
+
+/// Whether `--min-posts` leaves this item out of the results.
+fn is_suppressed(item_name: &ItemName, stats: &ItemStats, config: &RunConfig) -> bool {
+    let tagged_posts = stats.supply_posts + stats.demand_posts;
+    if tagged_posts >= config.min_posts_threshold {
+        return false;
+    }
+    if config.is_verbose {
+        eprintln!(
+            "Suppressing item '{}': {} supply/demand posts is below the minimum of {}.",
+            item_name, tagged_posts, config.min_posts_threshold
+        );
+    }
+    true
+}
+
+/// Whether `--min-confidence` leaves this item out of the results.
+fn is_below_min_confidence(item_name: &ItemName, stats: &ItemStats, config: &RunConfig) -> bool {
+    let Some(min_confidence) = config.min_confidence else {
+        return false;
+    };
+    let tier = config.confidence_tier(stats.prices.len());
+    if tier >= min_confidence {
+        return false;
+    }
+    if config.is_verbose {
+        eprintln!(
+            "Suppressing item '{}': {} prices is only {} confidence, below {}.",
+            item_name,
+            stats.prices.len(),
+            tier,
+            min_confidence
+        );
+    }
+    true
+}
+
+/// Dataset-wide values every item's analysis is measured against.
+struct DatasetContext {
+    /// Span of the whole dataset, used for the selling frequency.
+    total_days: f64,
+    run_time: DateTime<Utc>,
+    /// The newest in-window message, which the shock windows end at.
+    latest_trade: Option<DateTime<FixedOffset>>,
+    /// The highest raw liquidity of any item, which scores 100.
+    max_raw_liquidity: f64,
+}
+
+impl DatasetContext {
+    /// `all_trade_dates` must be sorted oldest first.
+    fn new(
+        item_data: &HashMap<ItemName, ItemStats>,
+        all_trade_dates: &[DateTime<FixedOffset>],
+        run_time: DateTime<Utc>,
+    ) -> Self {
+        DatasetContext {
+            total_days: trade_data_span(all_trade_dates).num_days() as f64,
+            run_time,
+            latest_trade: all_trade_dates.last().copied(),
+            max_raw_liquidity: item_data.values().map(raw_liquidity).fold(0.0, f64::max),
+        }
+    }
+}
+
+/// Computes the price statistics, trade chances and activity for a single item.
+fn analyze_item(
+    item_name: ItemName,
+    mut stats: ItemStats,
+    config: &RunConfig,
+    context: &DatasetContext,
+) -> ItemAnalysis {
+    let DatasetContext {
+        total_days,
+        run_time,
+        latest_trade,
+        max_raw_liquidity,
+    } = *context;
+    let variance = population_variance(&stats.prices);
+    let mean = mean_price(&stats.prices);
+    let std_dev = variance.map(f64::sqrt);
+    let weighted_median = weighted_median(&stats.prices, &stats.trade_dates, config.decay_rate);
+    let price_trend = price_trend(
+        &stats.prices,
+        &stats.trade_dates,
+        config.trend_stable_threshold,
+    );
+    let (dominant_price, price_uniformity) = dominant_price(&stats.prices);
+    if config.is_verbose && !stats.prices.is_empty() {
+        eprintln!(
+            "Price distribution for '{}' ({} prices):\n{}",
+            item_name,
+            stats.prices.len(),
+            ascii_histogram(&stats.prices, config.histogram_bins)
+        );
+    }
+    let market_saturation = saturation(stats.supply_posts, stats.demand_posts);
+    let total_volume = round_cents(stats.prices.iter().sum());
+    let (estimated_spread, spread_percentage, market_condition) = bid_ask_spread(&stats);
+    let liquidity_score = if max_raw_liquidity > 0.0 {
+        raw_liquidity(&stats) / max_raw_liquidity * 100.0
+    } else {
+        0.0
+    };
+    let rolling_avg_7d = rolling_average(&stats.prices, &stats.trade_dates, 7);
+    let rolling_avg_30d = rolling_average(&stats.prices, &stats.trade_dates, 30);
+    let momentum_30d = momentum_30d(&stats.prices, &stats.trade_dates, run_time);
+    let price_history = if config.include_history || config.history_csv.is_some() {
+        price_history(&stats)
+    } else {
+        Vec::new()
+    };
+    // Condition medians rely on `conditions` lining up with `prices`, so take them first.
+    let new_median = median_of_unsorted(&stats.prices_in_condition(Condition::New));
+    let used_median = median_of_unsorted(&stats.prices_in_condition(Condition::Used));
+
+    stats
+        .prices
+        .sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let median_price = price_at_percentile(&stats.prices, 50.0);
+    let mean_vs_median_skew = mean
+        .zip(median_price)
+        .filter(|(_, median)| *median != 0.0)
+        .map(|(mean, median)| (mean - median) / median * 100.0);
+    let fair_value = fair_value(&stats).or(median_price);
+    let (supply_shock, demand_spike) = match latest_trade {
+        // Without four full weeks of data the earlier weeks would look artificially quiet.
+        Some(latest) if total_days >= (SHOCK_WINDOW_WEEKS * 7) as f64 => (
+            is_activity_spike(&stats, PostKind::Supply, latest),
+            is_activity_spike(&stats, PostKind::Demand, latest),
+        ),
+        _ => (false, false),
+    };
+    let (p25, p75) = if stats.prices.len() < 4 {
+        (None, None)
+    } else {
+        (
+            price_at_percentile(&stats.prices, 25.0),
+            price_at_percentile(&stats.prices, 75.0),
+        )
+    };
+    let iqr = p25.zip(p75).map(|(low, high)| high - low);
+    let min_price = stats.prices.first().cloned();
+    let max_price = stats.prices.last().cloned();
+
+    let total_posts = stats.supply_posts + stats.demand_posts;
+
+    let buy_chance = if total_posts > 0 && stats.demand_posts > 0 {
+        (stats.demand_posts as f64 / total_posts as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    let sell_chance = if total_posts > 0 && stats.supply_posts > 0 {
+        (stats.supply_posts as f64 / total_posts as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    let frequency_str = if total_posts > 0 && total_days > 0.0 {
+        let trades_per_day = total_posts as f64 / total_days;
+        if trades_per_day >= 1.0 {
+            format!("{:.2} times/day", trades_per_day)
+        } else if trades_per_day * 7.0 >= 1.0 {
+            format!("{:.2} times/week", trades_per_day * 7.0)
+        } else if trades_per_day * 30.44 >= 1.0 {
+            format!("{:.2} times/month", trades_per_day * 30.44)
+        } else {
+            format!("Once every {:.0} days", 1.0 / trades_per_day)
+        }
+    } else {
+        "Infrequently/Not observed".to_string()
+    };
+
+    let first_trade = stats.trade_dates.iter().min().copied();
+    let last_trade = stats.trade_dates.iter().max().copied();
+    let observation_span_days = first_trade
+        .zip(last_trade)
+        .map(|(first, last)| last.signed_duration_since(first).num_seconds() as f64 / 86_400.0);
+    let days_since_last_trade =
+        last_trade.map(|last| run_time.signed_duration_since(last).num_seconds() as f64 / 86_400.0);
+    let activity_status = match days_since_last_trade {
+        Some(days) if days < config.active_threshold_days => "active",
+        Some(days) if days <= config.dormant_threshold_days => "dormant",
+        _ => "inactive",
+    };
+
+    let trade_completion_rate = if stats.supply_posts > 0 {
+        Some(stats.completed_posts as f64 / stats.supply_posts as f64)
+    } else {
+        None
+    };
+
+    ItemAnalysis {
+        item: item_name.into_inner(),
+        estimated_price: EstimatedPrice {
+            median: median_price,
+            mean,
+            mean_vs_median_skew,
+            min: min_price,
+            max: max_price,
+            variance,
+            std_dev,
+            weighted_median,
+            p25,
+            p75,
+            iqr,
+            is_range_derived: stats.range_derived_posts > 0,
+            median_unit_price: median_of_unsorted(&stats.unit_prices),
+            new_median,
+            used_median,
+        },
+        fair_value,
+        supply_demand: SupplyDemand {
+            supply_posts: stats.supply_posts,
+            demand_posts: stats.demand_posts,
+            swap_posts: stats.swap_posts,
+            completed_posts: stats.completed_posts,
+        },
+        estimated_trade_chances: TradeChance {
+            chance_to_buy: format!("{:.2}%", buy_chance),
+            chance_to_sell: format!("{:.2}%", sell_chance),
+        },
+        rough_selling_frequency: frequency_str,
+        trade_completion_rate,
+        first_seen: first_trade.map(|dt| dt.to_rfc3339()),
+        last_seen: last_trade.map(|dt| dt.to_rfc3339()),
+        observation_span_days,
+        last_trade_date: last_trade.map(|dt| dt.to_rfc3339()),
+        days_since_last_trade,
+        activity_status: activity_status.to_string(),
+        outliers_removed: stats.outliers_removed,
+        confidence_tier: config.confidence_tier(stats.prices.len()).to_string(),
+        total_volume,
+        estimated_spread,
+        spread_percentage,
+        market_condition: market_condition.to_string(),
+        typical_price_flexibility: price_flexibility(stats.flexible_posts, stats.firm_posts)
+            .to_string(),
+        condition_breakdown: stats.condition_breakdown.into_iter().collect(),
+        supply_shock,
+        demand_spike,
+        market_saturation,
+        liquidity_score,
+        // Needs every item's post count; set by `assign_popularity_ranks`.
+        popularity_rank: 0,
+        rolling_avg_7d,
+        rolling_avg_30d,
+        momentum_30d,
+        busiest_day: busiest_day(&stats.trade_dates).map(str::to_string),
+        hourly_activity: config
+            .include_hourly
+            .then(|| hourly_counts(&stats.trade_dates)),
+        price_trend: price_trend.to_string(),
+        price_uniformity,
+        dominant_price,
+        data_quality_warning: (stats.prices.len() >= UNIFORMITY_MIN_PRICES
+            && price_uniformity > HIGH_UNIFORMITY)
+            .then(|| "high_uniformity_may_indicate_spam_or_fixed_price".to_string()),
+        price_history,
+    }
+}
+
+/// Parses a record's date: `custom_format` first, then RFC 3339, RFC 2822 and
+/// [`FALLBACK_DATE_FORMATS`]. Formats without an offset are read as UTC.
+pub(crate) fn parse_trade_date(
+    raw: &str,
+    custom_format: Option<&str>,
+) -> Result<DateTime<FixedOffset>, SkipReason> {
+    let raw = raw.trim();
+    let as_utc = |naive: NaiveDateTime| naive.and_utc().fixed_offset();
+    if let Some(format) = custom_format {
+        if let Ok(date) = DateTime::parse_from_str(raw, format) {
+            return Ok(date);
+        }
+        if let Ok(naive) = NaiveDateTime::parse_from_str(raw, format) {
+            return Ok(as_utc(naive));
+        }
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(date);
+    }
+    if let Ok(date) = DateTime::parse_from_rfc2822(raw) {
+        return Ok(date);
+    }
+    if let Some(naive) = FALLBACK_DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(raw, format).ok())
+    {
+        return Ok(as_utc(naive));
+    }
+    // "2025-02-30T10:00:00Z" is a broken export rather than an unsupported format.
+    let looks_like_rfc3339 = raw.as_bytes().get(..11).is_some_and(|prefix| {
+        prefix.iter().enumerate().all(|(i, byte)| match i {
+            4 | 7 => *byte == b'-',
+            10 => byte.eq_ignore_ascii_case(&b'T'),
+            _ => byte.is_ascii_digit(),
+        })
+    });
+    Err(if looks_like_rfc3339 {
+        SkipReason::UnparseableRfc3339(raw.to_string())
+    } else {
+        SkipReason::UnparseableDate(raw.to_string())
+    })
+}
+
+/// Extracts the item, price(s) and post kind from a single record. Pure with respect
+/// to shared state so it can run on any thread.
+fn classify_record(record: &TradeRecord, matchers: &Matchers, config: &RunConfig) -> RecordOutcome {
+    let skip = |reason, trade_date| RecordOutcome::Skipped { reason, trade_date };
+
+    let Some(content) = &record.content else {
+        return skip(SkipReason::MissingContent, None);
+    };
+    let content_lower = content.to_lowercase();
+
+    let trade_date = match parse_trade_date(&record.date, matchers.date_format.as_deref()) {
+        Ok(trade_date) => trade_date,
+        Err(reason) => return skip(reason, None),
+    };
+
+    // Bounds are inclusive and compared against the date as written in the export.
+    let record_day = trade_date.date_naive();
+    let before_window = config.date_from.is_some_and(|from| record_day < from);
+    let after_window = config.date_to.is_some_and(|to| record_day > to);
+    if before_window || after_window {
+        return skip(SkipReason::DateRangeFiltered(record_day), None);
+    }
+
+    let mentions = find_items(matchers, &content_lower);
+    let Some(first_mention) = mentions.first() else {
+        return skip(SkipReason::NoItemFound, Some(trade_date));
+    };
+
+    // Blank out pack sizes so "10x bolts for 500" isn't priced at 10. Spaces of
+    // the same length keep the offsets of `price_text` in line with the content.
+    let mut price_text = content_lower.clone();
+    let packs: Vec<(Range<usize>, u32)> = matchers
+        .unit_quantity
+        .captures_iter(&content_lower)
+        .map(|caps| {
+            let span = caps.get(0).unwrap().range();
+            price_text.replace_range(span.clone(), &" ".repeat(span.len()));
+            let count = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+            (
+                span,
+                count.parse::<u32>().ok().filter(|&q| q > 0).unwrap_or(1),
+            )
+        })
+        .collect();
+
+    let priced = if mentions.len() == 1 {
+        let quantity = packs.first().map_or(1, |(_, quantity)| *quantity);
+        let Some((prices, is_range)) = single_item_prices(matchers, &price_text) else {
+            return skip(
+                SkipReason::NoPriceFound(first_mention.name.clone().into_inner()),
+                Some(trade_date),
+            );
+        };
+        vec![(first_mention.name.clone(), prices, quantity, is_range)]
+    } else {
+        // Each pack size belongs to whichever item it sits closest to.
+        let mut quantities = vec![None; mentions.len()];
+        for (span, quantity) in &packs {
+            if let Some(owner) =
+                (0..mentions.len()).min_by_key(|&i| span_gap(&mentions[i].span, span))
+            {
+                quantities[owner].get_or_insert(*quantity);
+            }
+        }
+        let priced: Vec<_> = nearest_prices(matchers, &mentions, &price_text)
+            .into_iter()
+            .map(|(i, prices)| {
+                let quantity = quantities[i].unwrap_or(1);
+                let is_range = prices.len() > 1;
+                (mentions[i].name.clone(), prices, quantity, is_range)
+            })
+            .collect();
+        if priced.is_empty() {
+            return skip(
+                SkipReason::NoPriceFound(first_mention.name.clone().into_inner()),
+                Some(trade_date),
+            );
+        }
+        priced
+    };
+
+    // A "sold" follow-up supersedes any sell keyword in the same message.
+    let post_kind = if matchers.sold.is_match(&content_lower) {
+        PostKind::Completed
+    } else if matchers.sell.is_match(&content_lower) {
+        PostKind::Supply
+    } else if matchers.buy.is_match(&content_lower) {
+        PostKind::Demand
+    } else if matchers.swap.is_match(&content_lower) {
+        PostKind::Swap
+    } else {
+        PostKind::Untagged
+    };
+    let is_flexible = matchers.flexible.is_match(&content_lower);
+    let is_firm = matchers.firm.is_match(&content_lower);
+    let condition = matchers
+        .condition
+        .find(&content_lower)
+        .map_or(Condition::Unknown, |m| Condition::from_keyword(m.as_str()));
+
+    RecordOutcome::Observed(
+        priced
+            .into_iter()
+            .map(|(item_name, prices, quantity, is_range)| Observation {
+                item_name,
+                prices: prices.into_iter().map(Price::from).collect(),
+                quantity,
+                is_range,
+                is_flexible,
+                is_firm,
+                condition,
+                trade_date,
+                post_kind,
+            })
+            .collect(),
+    )
+}
+
+/// The prices of a post naming a single item: both endpoints of a quoted range
+/// like "500-600k", otherwise the first price token.
+fn single_item_prices(matchers: &Matchers, price_text: &str) -> Option<(Vec<f64>, bool)> {
+    let range_prices = matchers
+        .range
+        .captures(price_text)
+        .and_then(|caps| range_endpoints(matchers, &caps));
+    if let Some(prices) = range_prices {
+        return Some((prices, true));
+    }
+    matchers
+        .price
+        .find(price_text)
+        .and_then(|m| matchers.price_parser.parse(m.as_str()))
+        .map(|p| (vec![p], false))
+}
+
+/// Both endpoints of a `matchers.range` match such as "500-600k", low first.
+fn range_endpoints(matchers: &Matchers, caps: &regex::Captures) -> Option<Vec<f64>> {
+    let (low_str, high_str) = (&caps[1], &caps[2]);
+    let high = matchers.price_parser.parse(high_str)?;
+    let mut low = matchers.price_parser.parse(low_str)?;
+    // Let "500-600k" borrow the upper bound's suffix, but keep "900-1.2k" as 900.
+    let low_has_suffix = low_str.ends_with(|c: char| c.is_ascii_alphabetic());
+    let high_suffix = high_str.chars().last().filter(char::is_ascii_alphabetic);
+    if let (false, Some(suffix)) = (low_has_suffix, high_suffix) {
+        let scaled = matchers
+            .price_parser
+            .parse(&format!("{}{}", low_str, suffix))?;
+        if scaled <= high {
+            low = scaled;
+        }
+    }
+    Some(vec![low, high])
+}
+
+/// Pairs the mentions of a multi-item post with prices, as `(mention index,
+/// prices)`: in order of mention, each item takes the closest price token not
+/// already taken, one after the item winning a tie. A bare whole number right
+/// before an item, as in a "3 catz" stock list, is a count rather than a price.
+/// Items left without a token get no price. A range such as "500-600k" is one
+/// token giving both endpoints, like a single-item post's range.
+fn nearest_prices(
+    matchers: &Matchers,
+    mentions: &[ItemMention],
+    price_text: &str,
+) -> Vec<(usize, Vec<f64>)> {
+    let is_count = |token: &regex::Match| {
+        token.as_str().bytes().all(|b| b.is_ascii_digit())
+            && mentions.iter().any(|mention| {
+                mention.span.start >= token.end()
+                    && price_text[token.end()..mention.span.start]
+                        .trim()
+                        .is_empty()
+            })
+    };
+    let mut tokens: Vec<(Range<usize>, Vec<f64>)> = matchers
+        .range
+        .captures_iter(price_text)
+        .filter_map(|caps| Some((caps.get(0)?.range(), range_endpoints(matchers, &caps)?)))
+        .collect();
+    let ranges: Vec<Range<usize>> = tokens.iter().map(|(span, _)| span.clone()).collect();
+    tokens.extend(
+        matchers
+            .price
+            .find_iter(price_text)
+            .filter(|token| !is_count(token))
+            .filter(|token| {
+                !ranges
+                    .iter()
+                    .any(|range| range.start < token.end() && token.start() < range.end)
+            })
+            .filter_map(|m| Some((m.range(), vec![matchers.price_parser.parse(m.as_str())?]))),
+    );
+    let mut priced = Vec::new();
+    for (i, mention) in mentions.iter().enumerate() {
+        let nearest = tokens
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (span, _))| {
+                (
+                    span_gap(&mention.span, span),
+                    span.start < mention.span.start,
+                )
+            })
+            .map(|(index, _)| index);
+        if let Some(index) = nearest {
+            priced.push((i, tokens.remove(index).1));
+        }
+    }
+    priced
+}
+
+/// Bytes between two spans of the same text; 0 when they touch or overlap.
+fn span_gap(a: &Range<usize>, b: &Range<usize>) -> usize {
+    if a.end <= b.start {
+        b.start - a.end
+    } else {
+        a.start.saturating_sub(b.end)
+    }
+}
+
+/// Where a post names an item.
+struct ItemMention {
+    name: ItemName,
+    span: Range<usize>,
+}
+
+/// Every item named in `content_lower`, in order of first mention. Only
+/// whole-word matches count, mirroring the `\b` boundaries of the regexes; a
+/// keyword inside a longer one starting at the same place or earlier (the
+/// "spring" of "hot spring") is ignored, as are items vetoed by one of their
+/// negative patterns and repeat mentions of an item.
+fn find_items(matchers: &Matchers, content_lower: &str) -> Vec<ItemMention> {
+    let items = &matchers.items;
+    let mut candidates: Vec<(Range<usize>, &str)> = items
+        .automaton
+        .find_overlapping_iter(content_lower)
+        .filter(|m| is_word_bounded(content_lower, m.start(), m.end()))
+        .map(|m| (m.range(), items.names[m.pattern().as_usize()].as_str()))
+        .filter(|(_, name)| !items.rejects(name, content_lower))
+        .collect();
+    candidates.sort_by_key(|(span, _)| (span.start, std::cmp::Reverse(span.end)));
+
+    let mut mentions: Vec<ItemMention> = Vec::new();
+    let mut covered_to = 0;
+    for (span, name) in candidates {
+        if span.start < covered_to {
+            continue;
+        }
+        covered_to = span.end;
+        if mentions.iter().all(|mention| mention.name.as_str() != name) {
+            mentions.push(ItemMention {
+                name: ItemName::from(name),
+                span,
+            });
+        }
+    }
+    mentions
+}
+
+/// Sums the counts in a Discord reactions cell such as `"👍 (5),🔥 (2)"` or
+/// `"👍 5, 🔥 2"`. Entries without a readable count contribute nothing.
+fn parse_reaction_count(reactions: &str) -> u32 {
+    reactions
+        .split(',')
+        .filter_map(|entry| {
+            let entry = entry.trim().trim_end_matches(')');
+            let digits_start = entry
+                .char_indices()
+                .rev()
+                .find(|(_, c)| !c.is_ascii_digit())
+                .map_or(0, |(i, c)| i + c.len_utf8());
+            entry[digits_start..].parse::<u32>().ok()
+        })
+        .sum()
+}
+
+fn is_word_bounded(text: &str, start: usize, end: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let before = text[..start]
+        .chars()
+        .next_back()
+        .is_none_or(|c| !is_word(c));
+    let after = text[end..].chars().next().is_none_or(|c| !is_word(c));
+    before && after
+}
+
+/// Expands the configured inputs into the list of CSV sources to read, replacing
+/// any directory with the `*.csv` files it contains.
+pub(crate) fn resolve_input_paths(config: &RunConfig) -> Result<Vec<String>, ParseError> {
+    if config.file_paths.is_empty() {
+        return Ok(vec!["-".to_string()]);
+    }
+    let mut input_paths = Vec::new();
+    for file_path in &config.file_paths {
+        let path = Path::new(file_path);
+        if !path.is_dir() {
+            input_paths.push(file_path.clone());
+            continue;
+        }
+        if config.is_verbose {
+            eprintln!("Scanning directory '{}' for CSV files...", file_path);
+        }
+        let mut discovered = Vec::new();
+        if let Err(e) = collect_csv_files(path, config.recursive, &mut discovered) {
+            if !config.is_quiet {
+                eprintln!("ERROR: Could not read directory '{}': {}", file_path, e);
+            }
+            return Err(ParseError::Io(e));
+        }
+        // Lexicographic order keeps chronologically named exports in sequence.
+        discovered.sort();
+        for csv_path in discovered {
+            let csv_path = csv_path.to_string_lossy().into_owned();
+            if config.is_verbose {
+                eprintln!("Discovered CSV file: '{}'", csv_path);
+            }
+            input_paths.push(csv_path);
+        }
+    }
+    Ok(input_paths)
+}
+
+/// Collects every `*.csv` file in `dir`, descending into subdirectories only when `recursive`.
+fn collect_csv_files(dir: &Path, recursive: bool, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if recursive {
+                collect_csv_files(&path, recursive, out)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "csv") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Spinner on stderr showing records read, records skipped and elapsed time.
+/// Hidden when stderr is not a terminal so piped and CI output stays clean.
+fn new_progress_bar() -> ProgressBar {
+    if !io::stderr().is_terminal() {
+        return ProgressBar::hidden();
+    }
+    let progress = ProgressBar::new_spinner();
+    progress.set_style(
+        ProgressStyle::with_template(
+            "{spinner} [{elapsed_precise}] {pos} records processed, {msg}",
+        )
+        .expect("progress template is valid"),
+    );
+    progress.set_message("0 skipped");
+    progress
+}
+
+/// Opens a CSV source; an empty path or the conventional `"-"` means stdin.
+fn open_source(file_path: &str, config: &RunConfig) -> Result<Box<dyn Read>, ParseError> {
+    let is_verbose = config.is_verbose;
+    if file_path.is_empty() || file_path == "-" {
+        if is_verbose {
+            eprintln!("Reading CSV data from stdin...");
+        }
+        Ok(Box::new(io::stdin()))
+    } else {
+        if is_verbose {
+            eprintln!("Attempting to open CSV file: '{}'", file_path);
+        }
+        match File::open(file_path) {
+            Ok(f) => {
+                if is_verbose {
+                    eprintln!("Successfully opened CSV file.");
+                }
+                if config.mmap {
+                    return Ok(map_or_buffer(file_path, f, config));
+                }
+                Ok(Box::new(f))
+            }
+            Err(e) => {
+                if !config.is_quiet {
+                    eprintln!("ERROR: Could not open file '{}': {}", file_path, e);
+                }
+                Err(ParseError::FileNotFound(e))
+            }
+        }
+    }
+}
+
+/// Memory-maps `file` for `--mmap`, falling back to reading it normally (with a
+/// warning) when it sits on a network share or the mapping fails.
+fn map_or_buffer(file_path: &str, file: File, config: &RunConfig) -> Box<dyn Read> {
+    let warn = |reason: &str| {
+        if !config.is_quiet {
+            eprintln!(
+                "WARNING: Not memory-mapping '{}' ({}); reading it normally instead.",
+                file_path, reason
+            );
+        }
+    };
+    if is_network_path(file_path) {
+        warn("it is on a network share");
+        return Box::new(file);
+    }
+    // SAFETY: the map is only read, and only while this run parses the file. A
+    // chat export being truncated by another process mid-run is not supported.
+    match unsafe { Mmap::map(&file) } {
+        Ok(map) => {
+            if config.is_verbose {
+                eprintln!("Memory-mapped {} bytes of '{}'.", map.len(), file_path);
+            }
+            Box::new(io::Cursor::new(map))
+        }
+        Err(e) => {
+            warn(&e.to_string());
+            Box::new(file)
+        }
+    }
+}
+
+/// A truncated network file can fault a mapped read instead of returning an error,
+/// so mapping is skipped for Windows UNC paths and for files on network mounts.
+fn is_network_path(file_path: &str) -> bool {
+    if cfg!(windows) && (file_path.starts_with(r"\\") || file_path.starts_with("//")) {
+        return true;
+    }
+    is_on_network_mount(file_path)
+}
+
+/// Looks up the filesystem holding `file_path` in `/proc/self/mounts`: the longest
+/// mount point containing the file is the one it lives on.
+#[cfg(target_os = "linux")]
+fn is_on_network_mount(file_path: &str) -> bool {
+    const NETWORK_FILESYSTEMS: &[&str] = &["nfs", "nfs4", "cifs", "smb3", "smbfs", "fuse.sshfs"];
+    let (Ok(path), Ok(mounts)) = (
+        fs::canonicalize(file_path),
+        fs::read_to_string("/proc/self/mounts"),
+    ) else {
+        return false;
+    };
+    // Each line is "device mount_point fs_type options ...".
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            Some((fields.next()?, fields.next()?))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .is_some_and(|(_, fs_type)| NETWORK_FILESYSTEMS.contains(&fs_type))
+}
+
+/// Other platforms rely on the mapping itself failing.
+#[cfg(not(target_os = "linux"))]
+fn is_on_network_mount(_file_path: &str) -> bool {
+    false
+}
+
+/// Human-readable name for an input path, used in the metadata block.
+fn display_path(file_path: &str) -> &str {
+    if file_path.is_empty() || file_path == "-" {
+        "stdin"
+    } else {
+        file_path
+    }
+}
+
+/// Weighted median of `prices`, where each price's weight decays exponentially with
+/// its age relative to the newest of the aligned `dates`.
+fn weighted_median(
+    prices: &[f64],
+    dates: &[DateTime<FixedOffset>],
+    decay_rate: f64,
+) -> Option<f64> {
+    let newest = dates.iter().max()?;
+    let mut weighted: Vec<(f64, f64)> = prices
+        .iter()
+        .zip(dates)
+        .map(|(price, date)| {
+            let days_old = newest.signed_duration_since(*date).num_seconds() as f64 / 86_400.0;
+            (*price, (-decay_rate * days_old).exp())
+        })
+        .collect();
+    weighted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let half_weight = weighted.iter().map(|(_, w)| w).sum::<f64>() / 2.0;
+    let mut cumulative = 0.0;
+    for (price, weight) in &weighted {
+        cumulative += weight;
+        if cumulative >= half_weight {
+            return Some(*price);
+        }
+    }
+    weighted.last().map(|(price, _)| *price)
+}
+
+/// Above this share of identical prices an item gets a `data_quality_warning`.
+const HIGH_UNIFORMITY: f64 = 0.7;
+/// Fewer prices than this are too few for uniformity to mean anything; two
+/// matching posts are not a pattern.
+const UNIFORMITY_MIN_PRICES: usize = 5;
+
+/// The most common price, counted to the cent so floating-point noise doesn't
+/// split equal prices, and the share of `prices` equal to it. Ties go to the
+/// lowest price; `(None, 0.0)` without prices.
+fn dominant_price(prices: &[f64]) -> (Option<f64>, f64) {
+    // Rounded as floats, since cents of a huge price overflow any integer type;
+    // adding zero folds -0.0 into 0.0.
+    let mut rounded: Vec<f64> = prices
+        .iter()
+        .map(|price| (price * 100.0).round() / 100.0 + 0.0)
+        .collect();
+    rounded.sort_by(f64::total_cmp);
+    let mut best: Option<(f64, usize)> = None;
+    for run in rounded.chunk_by(|a, b| a.total_cmp(b).is_eq()) {
+        // Strictly greater, so the lowest of equally common prices wins.
+        if best.is_none_or(|(_, count)| run.len() > count) {
+            best = Some((run[0], run.len()));
+        }
+    }
+    match best {
+        Some((price, count)) => (Some(price), count as f64 / prices.len() as f64),
+        None => (None, 0.0),
+    }
+}
+
+/// Spreads wider than this percentage of the asking price mark a `"wide_market"`.
+const WIDE_MARKET_SPREAD_PERCENT: f64 = 20.0;
+
+/// Weeks looked at by the shock detector: the latest one plus the baseline before it.
+const SHOCK_WINDOW_WEEKS: i64 = 4;
+/// How many times the baseline weekly average the latest week must exceed.
+const SHOCK_MULTIPLIER: f64 = 3.0;
+/// The latest week needs at least this many posts, so one post after a quiet
+/// month isn't reported as a shock.
+const SHOCK_MIN_POSTS: u32 = 3;
+
+/// Whether the week ending at `latest` had a burst of `kind` posts compared with
+/// the average of the three weeks before it.
+fn is_activity_spike(stats: &ItemStats, kind: PostKind, latest: DateTime<FixedOffset>) -> bool {
+    let mut weekly = [0u32; SHOCK_WINDOW_WEEKS as usize];
+    for (date, post_kind) in stats.trade_dates.iter().zip(&stats.post_kinds) {
+        if *post_kind != kind {
+            continue;
+        }
+        let age = latest.signed_duration_since(*date);
+        if age < Duration::zero() {
+            continue;
+        }
+        let week = (age.num_seconds() / (7 * 86_400)) as usize;
+        if let Some(count) = weekly.get_mut(week) {
+            *count += 1;
+        }
+    }
+    let recent = weekly[0];
+    let baseline = weekly[1..].iter().sum::<u32>() as f64 / (SHOCK_WINDOW_WEEKS - 1) as f64;
+    recent >= SHOCK_MIN_POSTS && recent as f64 > baseline * SHOCK_MULTIPLIER
+}
+
+fn fair_value(stats: &ItemStats) -> Option<f64> {
+    let supply_median = median_of_unsorted(&stats.supply_prices)?;
+    let demand_median = median_of_unsorted(&stats.demand_prices)?;
+    let total_posts = (stats.supply_posts + stats.demand_posts) as f64;
+    if total_posts == 0.0 {
+        return None;
+    }
+    Some(
+        supply_median * (stats.demand_posts as f64 / total_posts)
+            + demand_median * (stats.supply_posts as f64 / total_posts),
+    )
+}
+
+/// The spread, spread percentage and market condition from the item's per-side medians.
+fn bid_ask_spread(stats: &ItemStats) -> (Option<f64>, Option<f64>, &'static str) {
+    let (Some(ask), Some(bid)) = (
+        median_of_unsorted(&stats.supply_prices),
+        median_of_unsorted(&stats.demand_prices),
+    ) else {
+        return (None, None, "unknown");
+    };
+    let spread = ask - bid;
+    let percentage = (ask != 0.0).then(|| spread / ask * 100.0);
+    // A negative spread (buyers bidding above asks) is just as unsettled as a wide one.
+    let condition = match percentage {
+        Some(p) if p.abs() > WIDE_MARKET_SPREAD_PERCENT => "wide_market",
+        Some(_) => "normal",
+        None => "unknown",
+    };
+    (Some(spread), percentage, condition)
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Ratio of supply to demand posts, or `None` when nobody is buying.
+fn saturation(supply_posts: u32, demand_posts: u32) -> Option<f64> {
+    (demand_posts > 0).then(|| supply_posts as f64 / demand_posts as f64)
+}
+
+/// Mean of the prices posted within `window_days` of the newest aligned date.
+/// An item with less history than the window falls back to the plain mean.
+fn rolling_average(
+    prices: &[f64],
+    dates: &[DateTime<FixedOffset>],
+    window_days: i64,
+) -> Option<f64> {
+    let newest = dates.iter().max()?;
+    let cutoff = *newest - Duration::days(window_days);
+    let window: Vec<f64> = prices
+        .iter()
+        .zip(dates)
+        .filter(|(_, date)| **date >= cutoff)
+        .map(|(price, _)| *price)
+        .collect();
+    Some(window.iter().sum::<f64>() / window.len() as f64)
+}
+
+/// `(recent - older) / older * 100` for the medians of `prices` dated in the 30
+/// days up to `now` and in the 30 days before those. `prices` and `dates` must
+/// be aligned; `None` when either window is empty or the older median is zero.
+fn momentum_30d(
+    prices: &[f64],
+    dates: &[DateTime<FixedOffset>],
+    now: DateTime<Utc>,
+) -> Option<f64> {
+    let recent_start = now - Duration::days(30);
+    let older_start = now - Duration::days(60);
+    let (mut recent, mut older) = (Vec::new(), Vec::new());
+    for (&price, date) in prices.iter().zip(dates) {
+        if *date >= recent_start && *date <= now {
+            recent.push(price);
+        } else if *date >= older_start && *date < recent_start {
+            older.push(price);
+        }
+    }
+    let older_median = median_of_unsorted(&older).filter(|median| *median != 0.0)?;
+    let recent_median = median_of_unsorted(&recent)?;
+    Some(round_cents(
+        (recent_median - older_median) / older_median * 100.0,
+    ))
+}
+
+/// Monday first, matching [`weekday_counts`].
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// Average messages per weekend day over average per weekday, from Monday-first
+/// counts; 0.0 without any weekday messages.
+fn weekend_to_weekday_ratio(weekday_counts: &[u32; 7]) -> f64 {
+    let weekday_total: u32 = weekday_counts[..5].iter().sum();
+    let weekend_total: u32 = weekday_counts[5..].iter().sum();
+    if weekday_total > 0 {
+        (weekend_total as f64 / 2.0) / (weekday_total as f64 / 5.0)
+    } else {
+        0.0
+    }
+}
+
+/// Dates per day of the week, Monday first, using each date's own offset.
+fn weekday_counts(dates: &[DateTime<FixedOffset>]) -> [u32; 7] {
+    let mut counts = [0; 7];
+    for date in dates {
+        counts[date.weekday().num_days_from_monday() as usize] += 1;
+    }
+    counts
+}
+
+fn busiest_day(dates: &[DateTime<FixedOffset>]) -> Option<&'static str> {
+    let counts = weekday_counts(dates);
+    // `max_by_key` keeps the last maximum, so scan Sunday to Monday for the earliest.
+    let (index, count) = counts
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|(_, count)| **count)?;
+    (*count > 0).then(|| weekday_name(WEEKDAYS[index]))
+}
+
+/// Dates per UTC hour of the day.
+fn hourly_counts(dates: &[DateTime<FixedOffset>]) -> [u32; 24] {
+    let mut counts = [0; 24];
+    for date in dates {
+        counts[date.with_timezone(&Utc).hour() as usize] += 1;
+    }
+    counts
+}
+
+fn peak_hour(counts: &[u32; 24]) -> Option<u8> {
+    let (hour, count) = counts
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|(_, count)| **count)?;
+    (*count > 0).then_some(hour as u8)
+}
+
+/// Writes the weekly pattern Monday to Sunday rather than in hash order.
+pub(crate) fn serialize_weekly_pattern<S>(
+    pattern: &HashMap<String, u32>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::SerializeMap;
+    let mut map = serializer.serialize_map(Some(pattern.len()))?;
+    for day in WEEKDAYS {
+        let name = weekday_name(day);
+        if let Some(count) = pattern.get(name) {
+            map.serialize_entry(name, count)?;
+        }
+    }
+    map.end()
+}
+
+/// Classifies the direction of `prices` over time by comparing the mean of the
+/// older half of the observations (by aligned date) with the newer half.
+fn price_trend(
+    prices: &[f64],
+    dates: &[DateTime<FixedOffset>],
+    stable_threshold: f64,
+) -> &'static str {
+    if prices.len() < 4 {
+        return "insufficient_data";
+    }
+    let mut by_date: Vec<(DateTime<FixedOffset>, f64)> =
+        dates.iter().copied().zip(prices.iter().copied()).collect();
+    by_date.sort_by_key(|(date, _)| *date);
+
+    let (older, newer) = by_date.split_at(by_date.len() / 2);
+    let mean = |half: &[(DateTime<FixedOffset>, f64)]| {
+        half.iter().map(|(_, price)| price).sum::<f64>() / half.len() as f64
+    };
+    let (older_mean, newer_mean) = (mean(older), mean(newer));
+    let change = if older_mean > 0.0 {
+        (newer_mean - older_mean) / older_mean
+    } else {
+        newer_mean - older_mean
+    };
+    if change.abs() < stable_threshold {
+        "stable"
+    } else if change > 0.0 {
+        "rising"
+    } else {
+        "falling"
+    }
+}
+
+/// Classifies an item's posts by whether sellers say they'll negotiate.
+fn price_flexibility(flexible_posts: u32, firm_posts: u32) -> &'static str {
+    if flexible_posts + firm_posts == 0 {
+        return "unknown";
+    }
+    match flexible_posts.cmp(&firm_posts) {
+        std::cmp::Ordering::Greater => "mostly_flexible",
+        std::cmp::Ordering::Less => "mostly_firm",
+        std::cmp::Ordering::Equal => "mixed",
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    /// Builds [`ItemStats`] the way the record loop would fill them, for unit tests.
+    ///
+    /// `.supply()` / `.demand()` start a post of that kind, and each following `.price()`
+    /// is recorded against it at the most recent `.date()`.
+    pub(super) struct ItemStatsBuilder {
+        stats: ItemStats,
+        kind: PostKind,
+        date: DateTime<FixedOffset>,
+    }
+
+    impl ItemStatsBuilder {
+        pub(super) fn new() -> Self {
+            ItemStatsBuilder {
+                stats: ItemStats::default(),
+                kind: PostKind::Untagged,
+                date: DateTime::parse_from_rfc3339("2025-01-01T00:00:00+00:00").unwrap(),
+            }
+        }
+
+        pub(super) fn price(&mut self, price: f64) -> &mut Self {
+            self.stats.prices.push(price);
+            self.stats.unit_prices.push(price);
+            self.stats.trade_dates.push(self.date);
+            self.stats.post_kinds.push(self.kind);
+            self.stats.conditions.push(Condition::Unknown);
+            self.stats.authors.push(String::new());
+            match self.kind {
+                PostKind::Supply => self.stats.supply_prices.push(price),
+                PostKind::Demand => self.stats.demand_prices.push(price),
+                _ => {}
+            }
+            self
+        }
+
+        pub(super) fn supply(&mut self) -> &mut Self {
+            self.kind = PostKind::Supply;
+            self.stats.supply_posts += 1;
+            self
+        }
+
+        pub(super) fn demand(&mut self) -> &mut Self {
+            self.kind = PostKind::Demand;
+            self.stats.demand_posts += 1;
+            self
+        }
+
+        pub(super) fn date(&mut self, date: DateTime<FixedOffset>) -> &mut Self {
+            self.date = date;
+            self
+        }
+
+        pub(super) fn build(&mut self) -> ItemStats {
+            std::mem::take(&mut self.stats)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::ItemStatsBuilder;
+    use super::*;
+    use proptest::prelude::*;
+
+    fn day(n: u32) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(&format!("2025-01-{:02}T12:00:00+00:00", n)).unwrap()
+    }
+
+    #[test]
+    fn reject_outliers_keeps_parallel_lists_in_step() {
+        let mut stats = ItemStatsBuilder::new()
+            .supply()
+            .date(day(1))
+            .price(100.0)
+            .date(day(2))
+            .price(110.0)
+            .demand()
+            .date(day(3))
+            .price(105.0)
+            .date(day(4))
+            .price(95.0)
+            .supply()
+            .date(day(5))
+            .price(10_000.0)
+            .build();
+
+        stats.reject_outliers();
+
+        assert_eq!(stats.prices, vec![100.0, 110.0, 105.0, 95.0]);
+        assert_eq!(stats.trade_dates, vec![day(1), day(2), day(3), day(4)]);
+        assert_eq!(stats.supply_prices, vec![100.0, 110.0]);
+        assert_eq!(stats.demand_prices, vec![105.0, 95.0]);
+        assert_eq!(stats.outliers_removed, 1);
+        assert_eq!((stats.supply_posts, stats.demand_posts), (2, 1));
+    }
+
+    #[test]
+    fn reject_outliers_needs_four_prices() {
+        let mut stats = ItemStatsBuilder::new()
+            .supply()
+            .price(1.0)
+            .price(2.0)
+            .price(1_000.0)
+            .build();
+
+        stats.reject_outliers();
+
+        assert_eq!(stats.prices.len(), 3);
+        assert_eq!(stats.outliers_removed, 0);
+    }
+
+    #[test]
+    fn merge_interleaves_by_date_and_sums_counts() {
+        let mut a = ItemStatsBuilder::new()
+            .supply()
+            .date(day(1))
+            .price(100.0)
+            .date(day(3))
+            .price(300.0)
+            .build();
+        let b = ItemStatsBuilder::new()
+            .demand()
+            .date(day(2))
+            .price(200.0)
+            .date(day(3))
+            .price(310.0)
+            .build();
+
+        a.merge(b);
+
+        assert_eq!(a.prices, vec![100.0, 200.0, 300.0, 310.0]);
+        assert_eq!(a.trade_dates, vec![day(1), day(2), day(3), day(3)]);
+        assert_eq!(a.supply_prices, vec![100.0, 300.0]);
+        assert_eq!(a.demand_prices, vec![200.0, 310.0]);
+        assert_eq!((a.supply_posts, a.demand_posts), (1, 1));
+    }
+
+    #[test]
+    fn restored_snapshots_pick_up_where_they_left_off() {
+        let mut stats = ItemStatsBuilder::new()
+            .supply()
+            .date(day(1))
+            .price(100.0)
+            .demand()
+            .date(day(2))
+            .price(90.0)
+            .build();
+        stats.conditions[0] = Condition::NearMint;
+        stats.authors[1] = "trader1".to_string();
+        stats.condition_breakdown.insert("near_mint".to_string(), 1);
+
+        let snapshot = stats.snapshot();
+        assert!(snapshot.starts_with(r#"{"version":1,"stats":{"#));
+        let mut restored = ItemStats::restore(&snapshot).unwrap();
+
+        assert_eq!(restored.snapshot(), snapshot);
+        assert_eq!(restored.trade_dates, vec![day(1), day(2)]);
+        assert_eq!(
+            restored.post_kinds,
+            vec![PostKind::Supply, PostKind::Demand]
+        );
+        assert_eq!(restored.conditions[0], Condition::NearMint);
+        restored.merge(
+            ItemStatsBuilder::new()
+                .supply()
+                .date(day(3))
+                .price(110.0)
+                .build(),
+        );
+        assert_eq!(restored.prices, vec![100.0, 90.0, 110.0]);
+        assert_eq!(restored.supply_posts, 2);
+    }
+
+    #[test]
+    fn snapshots_from_another_version_are_refused() {
+        let snapshot = ItemStats::default()
+            .snapshot()
+            .replace(r#""version":1"#, r#""version":2"#);
+        let error = ItemStats::restore(&snapshot).unwrap_err();
+        assert!(error.to_string().contains("snapshot version 2"));
+        let error =
+            ItemStats::restore(&snapshot.replace(r#""version":2"#, r#""version":0"#)).unwrap_err();
+        assert!(error.to_string().contains("snapshot version 0"));
+        let error = ItemStats::restore("not json").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Could not read the ItemStats snapshot")
+        );
+        let error = ItemStats::restore(r#"{"version":1,"stats":{"prices":"many"}}"#).unwrap_err();
+        assert!(matches!(error, ParseError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn snapshots_with_misaligned_lists_are_refused() {
+        let error = ItemStats::restore(r#"{"version":1,"stats":{"prices":[5.0]}}"#).unwrap_err();
+        assert!(matches!(error, ParseError::InvalidArgument(_)));
+        assert!(error.to_string().contains("[1, 0, 0, 0, 0, 0]"));
+
+        let stats = ItemStatsBuilder::new()
+            .supply()
+            .price(100.0)
+            .price(110.0)
+            .build();
+        // One author dropped, as a cut-off write might leave it.
+        let truncated = stats
+            .snapshot()
+            .replace(r#""authors":["",""]"#, r#""authors":[""]"#);
+        assert_ne!(truncated, stats.snapshot());
+        assert!(ItemStats::restore(&truncated).is_err());
+        assert!(ItemStats::restore(&stats.snapshot()).is_ok());
+    }
+
+    #[test]
+    fn momentum_compares_the_last_30_days_with_the_30_before() {
+        let now = day(31).with_timezone(&Utc) + Duration::days(30);
+        // day(1) is 60 days back and day(31) 30 days: the older and recent windows.
+        let dates = [day(1), day(20), day(31), day(31) + Duration::days(10)];
+
+        assert_eq!(
+            momentum_30d(&[100.0, 100.0, 120.0, 130.0], &dates, now),
+            Some(25.0)
+        );
+        assert_eq!(momentum_30d(&[100.0, 100.0], &dates[..2], now), None);
+        assert_eq!(momentum_30d(&[120.0, 130.0], &dates[2..], now), None);
+    }
+
+    #[test]
+    fn dominant_price_counts_to_the_cent_and_prefers_the_lowest_on_ties() {
+        assert_eq!(
+            dominant_price(&[3000.0, 3000.004, 2999.996, 2500.0]),
+            (Some(3000.0), 0.75)
+        );
+        assert_eq!(dominant_price(&[20.0, 10.0, 20.0, 10.0]), (Some(10.0), 0.5));
+        assert_eq!(dominant_price(&[]), (None, 0.0));
+    }
+
+    #[test]
+    fn dominant_price_keeps_prices_too_large_for_integer_cents() {
+        let huge = 1.5498e19;
+        assert!(huge > (i64::MAX / 100) as f64);
+        assert_eq!(dominant_price(&[huge, huge, huge]), (Some(huge), 1.0));
+        assert_eq!(dominant_price(&[1e30, 1e30, huge]), (Some(1e30), 2.0 / 3.0));
+    }
+
+    #[test]
+    fn high_uniformity_needs_enough_prices_for_a_warning() {
+        let spammed = analyze_supply(&[3000.0, 3000.0, 3000.0, 3000.0, 2800.0]);
+        assert_eq!(spammed.price_uniformity, 0.8);
+        assert_eq!(
+            spammed.data_quality_warning.as_deref(),
+            Some("high_uniformity_may_indicate_spam_or_fixed_price")
+        );
+        assert_eq!(analyze_supply(&[3000.0, 3000.0]).data_quality_warning, None);
+        assert_eq!(
+            analyze_supply(&[3000.0, 3000.0, 3000.0, 2800.0, 2900.0]).data_quality_warning,
+            None
+        );
+    }
+
+    /// What `analyze_item` reports for `prices`, all posted as supply.
+    fn analyze_supply(prices: &[f64]) -> ItemAnalysis {
+        let mut builder = ItemStatsBuilder::new();
+        builder.supply();
+        for &price in prices {
+            builder.price(price);
+        }
+        let context = DatasetContext {
+            total_days: 0.0,
+            run_time: Utc::now(),
+            latest_trade: None,
+            max_raw_liquidity: 0.0,
+        };
+        analyze_item(
+            ItemName::from("Test Item"),
+            builder.build(),
+            &RunConfig::default(),
+            &context,
+        )
+    }
+
+    /// The price statistics `analyze_item` reports for `prices`, all posted as supply.
+    fn estimate(prices: &[f64]) -> EstimatedPrice {
+        analyze_supply(prices).estimated_price
+    }
+
+    fn prices() -> impl Strategy<Value = Vec<f64>> {
+        prop::collection::vec(0.01f64..1e9, 1..60)
+    }
+
+    /// `(day, price, is_supply)` posts, oldest first, as one file's stats would hold them.
+    fn posts() -> impl Strategy<Value = Vec<(u32, u32, bool)>> {
+        prop::collection::vec((1u32..29, 1u32..10_000, any::<bool>()), 0..20).prop_map(
+            |mut posts| {
+                posts.sort_by_key(|&(day, _, _)| day);
+                posts
+            },
+        )
+    }
+
+    fn stats_of(posts: &[(u32, u32, bool)]) -> ItemStats {
+        let mut builder = ItemStatsBuilder::new();
+        for &(n, price, is_supply) in posts {
+            if is_supply {
+                builder.supply();
+            } else {
+                builder.demand();
+            }
+            builder.date(day(n)).price(f64::from(price));
+        }
+        builder.build()
+    }
+
+    fn merged(mut a: ItemStats, b: ItemStats) -> ItemStats {
+        a.merge(b);
+        a
+    }
+
+    /// What the analysis reads from merged stats: every dated price with its side,
+    /// and the post counts.
+    fn aggregate(stats: &ItemStats) -> (Vec<(i64, u64, bool)>, u32, u32) {
+        let mut observations: Vec<(i64, u64, bool)> = (0..stats.prices.len())
+            .map(|i| {
+                (
+                    stats.trade_dates[i].timestamp(),
+                    stats.prices[i].to_bits(),
+                    stats.post_kinds[i] == PostKind::Supply,
+                )
+            })
+            .collect();
+        observations.sort();
+        (observations, stats.supply_posts, stats.demand_posts)
+    }
+
+    proptest! {
+        #[test]
+        fn median_of_odd_sorted_prices_is_the_middle_element(mut prices in prices()) {
+            if prices.len().is_multiple_of(2) {
+                prices.pop();
+            }
+            prices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            prop_assert_eq!(estimate(&prices).median, Some(prices[prices.len() / 2]));
+        }
+
+        #[test]
+        fn median_lies_between_min_and_max(prices in prices()) {
+            let estimate = estimate(&prices);
+            let (min, median, max) = (
+                estimate.min.unwrap(),
+                estimate.median.unwrap(),
+                estimate.max.unwrap(),
+            );
+            prop_assert!(min <= median && median <= max);
+        }
+
+        #[test]
+        fn identical_prices_have_zero_std_dev(price in 0.01f64..1e9, count in 2usize..60) {
+            prop_assert_eq!(estimate(&vec![price; count]).std_dev, Some(0.0));
+        }
+
+        #[test]
+        fn a_huge_outlier_keeps_the_median_below_the_max(mut prices in prices()) {
+            prices.push(1e15);
+            let estimate = estimate(&prices);
+            prop_assert!(estimate.median.unwrap() <= estimate.max.unwrap());
+            prop_assert_eq!(estimate.max, Some(1e15));
+        }
+
+        #[test]
+        fn merge_is_commutative(a in posts(), b in posts()) {
+            let ab = merged(stats_of(&a), stats_of(&b));
+            let ba = merged(stats_of(&b), stats_of(&a));
+            prop_assert_eq!(aggregate(&ab), aggregate(&ba));
+            prop_assert_eq!(ab.trade_dates, ba.trade_dates);
+        }
+
+        #[test]
+        fn merge_is_associative(a in posts(), b in posts(), c in posts()) {
+            let left = merged(merged(stats_of(&a), stats_of(&b)), stats_of(&c));
+            let right = merged(stats_of(&a), merged(stats_of(&b), stats_of(&c)));
+            prop_assert_eq!(aggregate(&left), aggregate(&right));
+            prop_assert_eq!(&left.prices, &right.prices);
+            prop_assert!(left.trade_dates.is_sorted());
+        }
+
+        #[test]
+        fn iqr_is_never_negative(prices in prices()) {
+            let estimate = estimate(&prices);
+            prop_assert_eq!(estimate.iqr.is_some(), prices.len() >= 4);
+            if let Some(iqr) = estimate.iqr {
+                prop_assert!(iqr >= 0.0);
+            }
+        }
     }
-    Ok(format!("{}{}", metadata_comments, yaml_items_output))
 }
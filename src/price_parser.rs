@@ -0,0 +1,163 @@
+// src/price_parser.rs
+
+//! Turning the price tokens found in trade messages into numbers.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+static DEFAULT_PARSER: LazyLock<PriceParser> = LazyLock::new(PriceParser::default);
+
+/// Converts raw price tokens such as `"2.5k"`, `"$1,200"` or `"3M"` into numbers.
+///
+/// `$` and thousands separators are stripped first, then the first multiplier
+/// pattern that matches is cut off and applied. Only positive, finite prices
+/// count, so zero, negative and unparseable tokens (including `""`) give `None`.
+///
+/// ```
+/// use goskateapiparser::price_parser::PriceParser;
+///
+/// assert_eq!(PriceParser::default().parse("$1,200"), Some(1200.0));
+/// assert_eq!(PriceParser::with_decimal_separator(',').parse("1.234,5k"), Some(1_234_500.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PriceParser {
+    /// Suffix patterns and the factor each applies, tried in order.
+    multipliers: Vec<(Regex, f64)>,
+    /// `.` for USD-style `1,234.56`; `,` for European `1.234,56`.
+    decimal_separator: char,
+}
+
+impl Default for PriceParser {
+    /// USD conventions: `.` decimals, `,` thousands, `k` for a thousand and `m`
+    /// for a million in either case.
+    fn default() -> Self {
+        PriceParser {
+            multipliers: vec![
+                (Regex::new(r"(?i)k$").unwrap(), 1000.0),
+                (Regex::new(r"(?i)m$").unwrap(), 1_000_000.0),
+            ],
+            decimal_separator: '.',
+        }
+    }
+}
+
+impl PriceParser {
+    /// The default multipliers with `separator` as the decimal point. The other of
+    /// `.` and `,` becomes the thousands separator.
+    pub fn with_decimal_separator(separator: char) -> Self {
+        PriceParser {
+            decimal_separator: separator,
+            ..PriceParser::default()
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Option<f64> {
+        let thousands_separator = if self.decimal_separator == ',' {
+            '.'
+        } else {
+            ','
+        };
+        let mut p_str: String = input
+            .chars()
+            .filter(|c| *c != '$' && *c != thousands_separator)
+            .map(|c| if c == self.decimal_separator { '.' } else { c })
+            .collect();
+        let mut multiplier = 1.0;
+        if let Some((range, factor)) = self
+            .multipliers
+            .iter()
+            .find_map(|(re, factor)| re.find(&p_str).map(|m| (m.range(), *factor)))
+        {
+            p_str.replace_range(range, "");
+            multiplier = factor;
+        }
+        p_str
+            .parse::<f64>()
+            .ok()
+            .map(|val| val * multiplier)
+            .filter(|val| val.is_finite() && *val > 0.0)
+    }
+}
+
+/// [`PriceParser::parse`] with the default USD conventions.
+pub fn parse_price(token: &str) -> Option<f64> {
+    DEFAULT_PARSER.parse(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PriceParser, parse_price};
+
+    #[test]
+    fn plain_integers() {
+        assert_eq!(parse_price("500"), Some(500.0));
+        assert_eq!(parse_price("7"), Some(7.0));
+    }
+
+    #[test]
+    fn decimal_values() {
+        assert_eq!(parse_price("12.5"), Some(12.5));
+        assert_eq!(parse_price("0.75"), Some(0.75));
+    }
+
+    #[test]
+    fn thousand_suffix_in_either_case() {
+        assert_eq!(parse_price("2k"), Some(2000.0));
+        assert_eq!(parse_price("2.5K"), Some(2500.0));
+    }
+
+    #[test]
+    fn million_suffix_in_either_case() {
+        assert_eq!(parse_price("3m"), Some(3_000_000.0));
+        assert_eq!(parse_price("1.5M"), Some(1_500_000.0));
+    }
+
+    #[test]
+    fn comma_separated_thousands() {
+        assert_eq!(parse_price("1,200"), Some(1200.0));
+        assert_eq!(parse_price("1,234,567"), Some(1_234_567.0));
+    }
+
+    #[test]
+    fn commas_are_stripped_before_the_suffix_applies() {
+        assert_eq!(parse_price("1,234.56k"), Some(1_234_560.0));
+    }
+
+    #[test]
+    fn dollar_sign_prefix() {
+        assert_eq!(parse_price("$40"), Some(40.0));
+        assert_eq!(parse_price("$1,200"), Some(1200.0));
+        assert_eq!(parse_price("$2k"), Some(2000.0));
+    }
+
+    #[test]
+    fn negative_numbers_are_rejected() {
+        assert_eq!(parse_price("-5"), None);
+        assert_eq!(parse_price("-2k"), None);
+    }
+
+    #[test]
+    fn zero_is_rejected() {
+        assert_eq!(parse_price("0"), None);
+        assert_eq!(parse_price("0k"), None);
+        assert_eq!(parse_price("$0.00"), None);
+    }
+
+    #[test]
+    fn empty_and_non_numeric_tokens_are_rejected() {
+        assert_eq!(parse_price(""), None);
+        assert_eq!(parse_price("k"), None);
+        assert_eq!(parse_price("$"), None);
+        assert_eq!(parse_price("1.2.3"), None);
+        assert_eq!(parse_price("inf"), None);
+    }
+
+    #[test]
+    fn comma_decimal_separator() {
+        let parser = PriceParser::with_decimal_separator(',');
+        assert_eq!(parser.parse("12,5"), Some(12.5));
+        assert_eq!(parser.parse("1.234,56"), Some(1234.56));
+        assert_eq!(parser.parse("2,5k"), Some(2500.0));
+        assert_eq!(parser.parse("$1.200"), Some(1200.0));
+    }
+}
@@ -0,0 +1,272 @@
+// src/serve.rs
+
+//! `--serve PORT`: the latest analysis over HTTP, re-run whenever the input
+//! changes, for a web dashboard to poll the parser as a sidecar.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::config::{OutputFormat, RunConfig};
+use crate::error::ParseError;
+use crate::output::render_output;
+use crate::parser::{AnalysisOutput, analyze_trades};
+use crate::watch::watch_inputs;
+
+/// How often the request loop wakes up to check `stop` while no requests arrive.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A response before it is turned into a `tiny_http` one.
+#[derive(Debug, PartialEq)]
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Reply {
+    fn json(status: u16, body: String) -> Self {
+        Reply {
+            status,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Reply::json(status, serde_json::json!({ "error": message }).to_string())
+    }
+}
+
+/// Analyzes the inputs, then serves the result on `addr` until `stop` is set:
+///
+/// * `GET /health` answers `200 OK`.
+/// * `GET /analysis` returns the whole analysis as JSON, metadata included.
+/// * `GET /items/{name}` returns one item, matched case-insensitively.
+///
+/// Every response allows any origin, so bind a loopback address such as
+/// `127.0.0.1` unless other machines should see the analysis. When the inputs
+/// are files, the analysis is re-run after each change and requests see the new
+/// result once it is ready; a failed re-run keeps the previous one. Only the
+/// first run's error is returned.
+pub fn serve_trade_analysis(
+    config: &RunConfig,
+    addr: SocketAddr,
+    stop: &AtomicBool,
+) -> Result<(), ParseError> {
+    let server =
+        Server::http(addr).map_err(|e| ParseError::ServerError(addr, io::Error::other(e)))?;
+    // `None` until the first run finishes; both threads borrow it within the scope.
+    let latest: RwLock<Option<AnalysisOutput>> = RwLock::new(None);
+    let json_config = RunConfig {
+        output_format: OutputFormat::Json,
+        summary: false,
+        is_verbose: false,
+        ..config.clone()
+    };
+    let publish = |analysis| {
+        *latest.write().unwrap_or_else(|e| e.into_inner()) = Some(analysis);
+    };
+
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            while !stop.load(Ordering::SeqCst) {
+                let request = match server.recv_timeout(STOP_POLL_INTERVAL) {
+                    Ok(Some(request)) => request,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        if !config.is_quiet {
+                            eprintln!("Warning: could not receive an HTTP request: {}", e);
+                        }
+                        continue;
+                    }
+                };
+                let reply = {
+                    let analysis = latest.read().unwrap_or_else(|e| e.into_inner());
+                    match analysis.as_ref() {
+                        Some(analysis) => {
+                            route(request.method(), request.url(), analysis, &json_config)
+                        }
+                        None => Reply::error(503, "The first analysis is still running"),
+                    }
+                };
+                respond(request, reply, config);
+            }
+        });
+
+        let result = if config.file_paths.iter().any(|path| path != "-") {
+            watch_inputs(config, stop, |is_rerun| {
+                match analyze_trades(config) {
+                    Ok(analysis) => publish(analysis),
+                    Err(e) if is_rerun => {
+                        if !config.is_quiet {
+                            eprintln!(
+                                "Warning: re-run failed, still serving the previous analysis: {}",
+                                e
+                            );
+                        }
+                    }
+                    Err(e) => return Err(e),
+                }
+                Ok(())
+            })
+        } else {
+            // Stdin can only be read once, so its analysis is served as is.
+            analyze_trades(config).map(|analysis| {
+                publish(analysis);
+                while !stop.load(Ordering::SeqCst) {
+                    thread::sleep(STOP_POLL_INTERVAL);
+                }
+            })
+        };
+        // Let the request loop exit when the first run failed.
+        stop.store(true, Ordering::SeqCst);
+        result
+    })
+}
+
+/// Picks the reply for one request.
+fn route(method: &Method, url: &str, analysis: &AnalysisOutput, json_config: &RunConfig) -> Reply {
+    if *method == Method::Options {
+        return Reply {
+            status: 204,
+            content_type: "text/plain",
+            body: String::new(),
+        };
+    }
+    if *method != Method::Get {
+        return Reply::error(405, "Only GET requests are supported");
+    }
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    match path.trim_end_matches('/') {
+        "/health" => Reply {
+            status: 200,
+            content_type: "text/plain",
+            body: "OK".to_string(),
+        },
+        "/analysis" => match render_output(analysis, json_config) {
+            Ok(json) => Reply::json(200, json),
+            Err(e) => Reply::error(500, &e.to_string()),
+        },
+        path => match path.strip_prefix("/items/") {
+            Some(name) => {
+                let name = percent_decode(name);
+                let item = analysis
+                    .items
+                    .iter()
+                    .find(|item| item.item == name)
+                    .or_else(|| {
+                        analysis
+                            .items
+                            .iter()
+                            .find(|item| item.item.eq_ignore_ascii_case(&name))
+                    });
+                match item.map(serde_json::to_string) {
+                    Some(Ok(json)) => Reply::json(200, json),
+                    Some(Err(e)) => Reply::error(500, &e.to_string()),
+                    None => Reply::error(404, &format!("No item named '{}'", name)),
+                }
+            }
+            None => Reply::error(404, "Not found; try /analysis, /items/{name} or /health"),
+        },
+    }
+}
+
+/// Sends `reply` with the CORS headers that let any dashboard fetch it.
+fn respond(request: Request, reply: Reply, config: &RunConfig) {
+    let headers = [
+        ("Content-Type", reply.content_type),
+        ("Access-Control-Allow-Origin", "*"),
+        ("Access-Control-Allow-Methods", "GET, OPTIONS"),
+        ("Access-Control-Allow-Headers", "*"),
+    ];
+    let mut response = Response::from_string(reply.body).with_status_code(reply.status);
+    for (name, value) in headers {
+        if let Ok(header) = Header::from_bytes(name, value) {
+            response.add_header(header);
+        }
+    }
+    if let Err(e) = request.respond(response)
+        && config.is_verbose
+    {
+        eprintln!("Could not send an HTTP response: {}", e);
+    }
+}
+
+/// Decodes `%XX` escapes such as the `%20` in `/items/Hot%20Spring`; malformed
+/// escapes are kept as written.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_analysis() -> (AnalysisOutput, RunConfig) {
+        let config = RunConfig {
+            file_paths: vec!["tests/fixtures/normal_trades.csv".to_string()],
+            is_quiet: true,
+            output_format: OutputFormat::Json,
+            ..RunConfig::default()
+        };
+        (analyze_trades(&config).unwrap(), config)
+    }
+
+    #[test]
+    fn routes_health_analysis_and_items() {
+        let (analysis, config) = fixture_analysis();
+        let get = |url| route(&Method::Get, url, &analysis, &config);
+
+        assert_eq!(get("/health").body, "OK");
+        let whole: serde_json::Value = serde_json::from_str(&get("/analysis").body).unwrap();
+        assert_eq!(whole["items"].as_array().unwrap().len(), 3);
+        assert!(whole["metadata"].is_object());
+
+        let item = get("/items/hot%20spring?fields=all");
+        assert_eq!(item.status, 200);
+        let item: serde_json::Value = serde_json::from_str(&item.body).unwrap();
+        assert_eq!(item["item"], "Hot Spring");
+
+        assert_eq!(get("/items/Nope").status, 404);
+        assert_eq!(get("/elsewhere").status, 404);
+        assert_eq!(
+            route(&Method::Post, "/analysis", &analysis, &config).status,
+            405
+        );
+        assert_eq!(
+            route(&Method::Options, "/analysis", &analysis, &config).status,
+            204
+        );
+    }
+
+    #[test]
+    fn percent_decoding_keeps_malformed_escapes() {
+        assert_eq!(percent_decode("Hot%20Spring"), "Hot Spring");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+}
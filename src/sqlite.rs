@@ -0,0 +1,102 @@
+// src/sqlite.rs
+
+//! `--sqlite PATH`: appends each run's items to a SQLite database so prices can
+//! be tracked across runs with plain SQL, and `--sqlite-query` to read them back.
+
+use chrono::Utc;
+use rusqlite::Connection;
+use rusqlite::types::ValueRef;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::error::ParseError;
+use crate::parser::AnalysisOutput;
+
+/// One row per item per run; `run_timestamp` is the run's Unix time in seconds.
+const CREATE_ITEMS_TABLE: &str = "CREATE TABLE IF NOT EXISTS items (
+    run_id TEXT NOT NULL,
+    item_name TEXT NOT NULL,
+    median_price REAL,
+    min_price REAL,
+    max_price REAL,
+    supply_posts INT NOT NULL,
+    demand_posts INT NOT NULL,
+    run_timestamp INT NOT NULL
+)";
+
+/// The rows returned by [`query_sqlite`], with every value kept as SQLite typed it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+/// Appends every item of `analysis` to the `items` table of the database at
+/// `path`, creating the file and table when missing, and returns the new run's
+/// `run_id`: the current UTC time to the microsecond, such as
+/// `20250816T120000.123456Z`, so ids sort in run order.
+pub fn save_to_sqlite(
+    analysis: &AnalysisOutput,
+    path: impl AsRef<Path>,
+) -> Result<String, ParseError> {
+    let mut connection = Connection::open(path)?;
+    connection.execute(CREATE_ITEMS_TABLE, ())?;
+    let run_id = Utc::now().format("%Y%m%dT%H%M%S%.6fZ").to_string();
+    let run_timestamp = analysis.metadata.parser_run_utc_epoch;
+
+    // One transaction, so a failed run leaves no partial rows behind.
+    let transaction = connection.transaction()?;
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO items (run_id, item_name, median_price, min_price, max_price, \
+             supply_posts, demand_posts, run_timestamp) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for item in &analysis.items {
+            insert.execute((
+                &run_id,
+                &item.item,
+                item.estimated_price.median,
+                item.estimated_price.min,
+                item.estimated_price.max,
+                item.supply_demand.supply_posts,
+                item.supply_demand.demand_posts,
+                run_timestamp,
+            ))?;
+        }
+    }
+    transaction.commit()?;
+    Ok(run_id)
+}
+
+/// Runs `sql` against the database at `path` and collects every row it returns.
+/// Statements that return no rows, such as a `DELETE`, give an empty result.
+pub fn query_sqlite(path: impl AsRef<Path>, sql: &str) -> Result<QueryResult, ParseError> {
+    let connection = Connection::open(path)?;
+    let mut statement = connection.prepare(sql)?;
+    let columns: Vec<String> = statement
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let mut rows = Vec::new();
+    let mut cursor = statement.query(())?;
+    while let Some(row) = cursor.next()? {
+        let values = (0..columns.len())
+            .map(|i| row.get_ref(i).map(json_value))
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.push(values);
+    }
+    Ok(QueryResult { columns, rows })
+}
+
+/// A SQLite value as JSON; blobs, which the `items` table never holds, are shown
+/// as `<N bytes>`.
+fn json_value(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(n) => n.into(),
+        ValueRef::Real(x) => x.into(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned().into(),
+        ValueRef::Blob(bytes) => format!("<{} bytes>", bytes.len()).into(),
+    }
+}
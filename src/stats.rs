@@ -0,0 +1,72 @@
+// src/stats.rs
+
+//! Order statistics over price lists, shared by the per-item analysis and
+//! available to library callers working with raw prices.
+
+/// The value at percentile `p` (0.0 to 100.0) of the ascending slice `sorted`.
+/// Positions that fall between two prices are linearly interpolated, so the
+/// 50th percentile of an even-length slice is the mean of its middle pair.
+/// `None` when the slice is empty or `p` is outside 0 to 100.
+///
+/// ```
+/// use goskateapiparser::price_at_percentile;
+///
+/// let prices = [10.0, 20.0, 30.0, 40.0];
+/// assert_eq!(price_at_percentile(&prices, 50.0), Some(25.0));
+/// assert_eq!(price_at_percentile(&prices, 100.0), Some(40.0));
+/// ```
+pub fn price_at_percentile(sorted: &[f64], p: f64) -> Option<f64> {
+    if sorted.is_empty() || !(0.0..=100.0).contains(&p) {
+        return None;
+    }
+    let position = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64))
+}
+
+/// Median of `prices` in any order; `None` when empty.
+pub(crate) fn median_of_unsorted(prices: &[f64]) -> Option<f64> {
+    let mut prices = prices.to_vec();
+    prices.sort_by(|x, y| x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal));
+    price_at_percentile(&prices, 50.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{median_of_unsorted, price_at_percentile};
+
+    #[test]
+    fn odd_length_median_is_the_middle_price() {
+        assert_eq!(price_at_percentile(&[1.0, 5.0, 9.0], 50.0), Some(5.0));
+    }
+
+    #[test]
+    fn quartiles_interpolate_between_neighbours() {
+        let prices = [100.0, 200.0, 300.0, 400.0, 500.0, 600.0];
+        assert_eq!(price_at_percentile(&prices, 25.0), Some(225.0));
+        assert_eq!(price_at_percentile(&prices, 75.0), Some(475.0));
+    }
+
+    #[test]
+    fn extremes_are_the_first_and_last_prices() {
+        let prices = [3.0, 7.0, 11.0];
+        assert_eq!(price_at_percentile(&prices, 0.0), Some(3.0));
+        assert_eq!(price_at_percentile(&prices, 100.0), Some(11.0));
+        assert_eq!(price_at_percentile(&[42.0], 90.0), Some(42.0));
+    }
+
+    #[test]
+    fn empty_slices_and_out_of_range_percentiles_give_none() {
+        assert_eq!(price_at_percentile(&[], 50.0), None);
+        assert_eq!(price_at_percentile(&[1.0, 2.0], -1.0), None);
+        assert_eq!(price_at_percentile(&[1.0, 2.0], 100.5), None);
+        assert_eq!(price_at_percentile(&[1.0, 2.0], f64::NAN), None);
+    }
+
+    #[test]
+    fn median_of_unsorted_averages_the_middle_pair() {
+        assert_eq!(median_of_unsorted(&[40.0, 10.0, 30.0, 20.0]), Some(25.0));
+        assert_eq!(median_of_unsorted(&[]), None);
+    }
+}
@@ -0,0 +1,93 @@
+// src/summary.rs
+
+//! A short market snapshot distilled from a full analysis, for `--summary`.
+
+use serde::Serialize;
+
+use crate::parser::{AnalysisOutput, ItemAnalysis};
+
+/// How many items `top_items` lists.
+const TOP_ITEM_COUNT: usize = 5;
+
+/// The global figures of an [`AnalysisOutput`] plus a handful of standout items.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnalysisSummary {
+    pub total_parsing_time_ms: u128,
+    pub overall_trade_data_span_days: f64,
+    pub overall_trade_data_span_weeks: f64,
+    pub overall_trade_data_span_months: f64,
+    pub global_volume: f64,
+    pub market_index: Option<f64>,
+    pub estimated_weekly_volume_usd: Option<f64>,
+    pub weekend_to_weekday_ratio: f64,
+    pub peak_hour_utc: Option<u8>,
+    /// Number of items in the full analysis.
+    pub items_analyzed: usize,
+    /// Highest median price.
+    pub most_expensive: Option<ItemAnalysis>,
+    /// Most supply + demand posts.
+    pub most_traded: Option<ItemAnalysis>,
+    pub highest_sell_chance: Option<ItemAnalysis>,
+    pub highest_buy_chance: Option<ItemAnalysis>,
+    /// The five items with the most supply + demand posts, busiest first.
+    pub top_items: Vec<ItemAnalysis>,
+}
+
+/// Picks the standout items from `analysis`. Ties go to the item listed first.
+pub fn summarize(analysis: &AnalysisOutput) -> AnalysisSummary {
+    let items = &analysis.items;
+    let mut by_volume: Vec<&ItemAnalysis> = items.iter().collect();
+    // Stable, so equally traded items keep the analysis order.
+    by_volume.sort_by_key(|item| std::cmp::Reverse(volume(item)));
+
+    AnalysisSummary {
+        total_parsing_time_ms: analysis.total_parsing_time_ms,
+        overall_trade_data_span_days: analysis.overall_trade_data_span_days,
+        overall_trade_data_span_weeks: analysis.overall_trade_data_span_weeks,
+        overall_trade_data_span_months: analysis.overall_trade_data_span_months,
+        global_volume: analysis.global_volume,
+        market_index: analysis.market_index,
+        estimated_weekly_volume_usd: analysis.estimated_weekly_volume_usd,
+        weekend_to_weekday_ratio: analysis.weekend_to_weekday_ratio,
+        peak_hour_utc: analysis.peak_hour_utc,
+        items_analyzed: items.len(),
+        most_expensive: best_by(items, |item| item.estimated_price.median),
+        most_traded: by_volume.first().map(|item| (*item).clone()),
+        highest_sell_chance: best_by(items, |item| {
+            chance_value(&item.estimated_trade_chances.chance_to_sell)
+        }),
+        highest_buy_chance: best_by(items, |item| {
+            chance_value(&item.estimated_trade_chances.chance_to_buy)
+        }),
+        top_items: by_volume
+            .into_iter()
+            .take(TOP_ITEM_COUNT)
+            .map(ItemAnalysis::clone)
+            .collect(),
+    }
+}
+
+fn volume(item: &ItemAnalysis) -> u32 {
+    item.supply_demand.supply_posts + item.supply_demand.demand_posts
+}
+
+/// Chances are pre-formatted as `"12.34%"`; anything else has no value.
+fn chance_value(chance: &str) -> Option<f64> {
+    chance.trim_end_matches('%').parse().ok()
+}
+
+/// The first item with the largest `key`, ignoring items without one.
+fn best_by(
+    items: &[ItemAnalysis],
+    key: impl Fn(&ItemAnalysis) -> Option<f64>,
+) -> Option<ItemAnalysis> {
+    let mut best: Option<(&ItemAnalysis, f64)> = None;
+    for item in items {
+        if let Some(value) = key(item)
+            && best.is_none_or(|(_, best_value)| value > best_value)
+        {
+            best = Some((item, value));
+        }
+    }
+    best.map(|(item, _)| item.clone())
+}
@@ -0,0 +1,113 @@
+// src/types.rs
+
+//! Newtypes that keep prices, author IDs and item names from being mixed up.
+//! Each serializes exactly like the value it wraps.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, Div};
+
+/// A listed price in in-game currency.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Price(pub f64);
+
+impl From<f64> for Price {
+    fn from(value: f64) -> Self {
+        Price(value)
+    }
+}
+
+impl From<Price> for f64 {
+    fn from(price: Price) -> Self {
+        price.0
+    }
+}
+
+impl Add for Price {
+    type Output = Price;
+
+    fn add(self, other: Price) -> Price {
+        Price(self.0 + other.0)
+    }
+}
+
+/// Scaling a price, e.g. a sum divided by a count for a mean.
+impl Div<f64> for Price {
+    type Output = Price;
+
+    fn div(self, divisor: f64) -> Price {
+        Price(self.0 / divisor)
+    }
+}
+
+impl Sum for Price {
+    fn sum<I: Iterator<Item = Price>>(iter: I) -> Price {
+        Price(iter.map(|price| price.0).sum())
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A Discord user ID, as exported in the `AuthorID` column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AuthorId(pub u64);
+
+impl From<u64> for AuthorId {
+    fn from(id: u64) -> Self {
+        AuthorId(id)
+    }
+}
+
+impl fmt::Display for AuthorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The canonical name of a tradeable item, e.g. `"Hot Spring"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ItemName(pub String);
+
+impl ItemName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl From<String> for ItemName {
+    fn from(name: String) -> Self {
+        ItemName(name)
+    }
+}
+
+impl From<&str> for ItemName {
+    fn from(name: &str) -> Self {
+        ItemName(name.to_string())
+    }
+}
+
+/// Lets maps keyed by `ItemName` be queried with a plain `&str`.
+impl Borrow<str> for ItemName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ItemName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
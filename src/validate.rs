@@ -0,0 +1,91 @@
+// src/validate.rs
+
+//! `validate`: checking that a file looks like a chat export before analyzing it.
+
+use csv::ReaderBuilder;
+use std::fs::File;
+use std::io::BufReader;
+
+use crate::error::ParseError;
+use crate::parser::{TradeRecord, parse_trade_date};
+
+/// The header columns every DiscordChatExporter CSV has.
+pub const REQUIRED_COLUMNS: &[&str] = &[
+    "AuthorID",
+    "Author",
+    "Date",
+    "Content",
+    "Attachments",
+    "Reactions",
+];
+
+/// How many data rows are checked; the schema rarely changes partway through an export.
+pub const SAMPLE_ROWS: usize = 100;
+
+/// What [`validate_csv`] found in one file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationReport {
+    pub path: String,
+    /// Required columns absent from the header. Rows are not checked when any are.
+    pub missing_columns: Vec<String>,
+    pub rows_checked: usize,
+    pub row_errors: Vec<RowError>,
+}
+
+/// A problem with one sampled row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowError {
+    /// 1-based line number, the header being line 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.missing_columns.is_empty() && self.row_errors.is_empty()
+    }
+}
+
+/// Checks the header of `path` for [`REQUIRED_COLUMNS`], then reads up to
+/// [`SAMPLE_ROWS`] rows, reporting rows that don't deserialize (such as a
+/// non-numeric `AuthorID`) or whose date is unparseable. `date_format` is tried
+/// first, as with `--date-format`.
+pub fn validate_csv(path: &str, date_format: Option<&str>) -> Result<ValidationReport, ParseError> {
+    let file = File::open(path).map_err(ParseError::FileNotFound)?;
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(true)
+        .from_reader(BufReader::new(file));
+    let headers = rdr.headers()?.clone();
+    let missing_columns: Vec<String> = REQUIRED_COLUMNS
+        .iter()
+        .filter(|column| !headers.iter().any(|header| header == **column))
+        .map(|column| column.to_string())
+        .collect();
+
+    let mut report = ValidationReport {
+        path: path.to_string(),
+        missing_columns,
+        rows_checked: 0,
+        row_errors: Vec::new(),
+    };
+    if !report.missing_columns.is_empty() {
+        return Ok(report);
+    }
+    for (i, result) in rdr
+        .deserialize::<TradeRecord>()
+        .take(SAMPLE_ROWS)
+        .enumerate()
+    {
+        report.rows_checked += 1;
+        let line = i + 2;
+        let message = match result {
+            Ok(record) => match parse_trade_date(&record.date, date_format) {
+                Ok(_) => continue,
+                Err(reason) => reason.describe(),
+            },
+            Err(e) => e.to_string(),
+        };
+        report.row_errors.push(RowError { line, message });
+    }
+    Ok(report)
+}
@@ -0,0 +1,157 @@
+// src/watch.rs
+
+//! Re-running the analysis whenever an input CSV changes, for live channel monitoring.
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::config::RunConfig;
+use crate::error::ParseError;
+use crate::parser::run_trade_analysis_to;
+
+/// Quiet period after the last change before re-running, so a half-written export
+/// isn't parsed mid-write.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// How often the loop wakes up to check `stop` while no events arrive.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Runs the analysis once, then again after every change to the configured inputs,
+/// separating each run's output with a `---` line. Returns once `stop` is set.
+///
+/// A failed re-run is reported on stderr and the watch carries on; only the first
+/// run's error is returned.
+pub fn watch_trade_analysis(
+    config: &RunConfig,
+    out: &mut impl Write,
+    stop: &AtomicBool,
+) -> Result<(), ParseError> {
+    watch_inputs(config, stop, |is_rerun| {
+        if !is_rerun {
+            run_trade_analysis_to(config, out)?;
+            return Ok(out.flush()?);
+        }
+        writeln!(out, "---")?;
+        if let Err(e) = run_trade_analysis_to(config, out)
+            && !config.is_quiet
+        {
+            eprintln!("Warning: re-run failed, waiting for the next change: {}", e);
+        }
+        Ok(out.flush()?)
+    })
+}
+
+/// Calls `run(false)` once the inputs are being watched, then `run(true)` after
+/// every debounced change to them, until `stop` is set. Errors from `run` end the
+/// watch, so callers that want to carry on after a failed re-run must handle it.
+pub(crate) fn watch_inputs(
+    config: &RunConfig,
+    stop: &AtomicBool,
+    mut run: impl FnMut(bool) -> Result<(), ParseError>,
+) -> Result<(), ParseError> {
+    let targets = watch_targets(config)?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = RecommendedWatcher::new(sender, notify::Config::default())?;
+    // Exporters often replace the file rather than write in place, which would orphan
+    // a watch on the file itself, so watch its directory and filter by name.
+    let mut watched_dirs = HashSet::new();
+    for target in &targets {
+        let (dir, mode) = match target {
+            WatchTarget::File(path) => (parent_dir(path), RecursiveMode::NonRecursive),
+            WatchTarget::Dir(path) if config.recursive => (path.clone(), RecursiveMode::Recursive),
+            WatchTarget::Dir(path) => (path.clone(), RecursiveMode::NonRecursive),
+        };
+        if watched_dirs.insert(dir.clone()) {
+            watcher.watch(&dir, mode)?;
+        }
+    }
+
+    run(false)?;
+    if config.is_verbose {
+        eprintln!("Watching for changes to the input; press Ctrl+C to stop.");
+    }
+
+    while !stop.load(Ordering::SeqCst) {
+        match receiver.recv_timeout(STOP_POLL_INTERVAL) {
+            Ok(Ok(event)) if is_relevant(&event, &targets) => {}
+            Ok(Ok(_)) | Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Ok(Err(e)) => {
+                if !config.is_quiet {
+                    eprintln!("Warning: file watcher error: {}", e);
+                }
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+        // Swallow the burst of events a single save produces.
+        while receiver.recv_timeout(DEBOUNCE).is_ok() {}
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+
+        if config.is_verbose {
+            eprintln!("Input changed, re-running analysis...");
+        }
+        run(true)?;
+    }
+    Ok(())
+}
+
+enum WatchTarget {
+    File(PathBuf),
+    Dir(PathBuf),
+}
+
+fn watch_targets(config: &RunConfig) -> Result<Vec<WatchTarget>, ParseError> {
+    let paths: Vec<&String> = config.file_paths.iter().filter(|p| *p != "-").collect();
+    if paths.is_empty() {
+        return Err(ParseError::InvalidArgument(
+            "--watch needs at least one --data file; stdin cannot be watched.".to_string(),
+        ));
+    }
+    paths
+        .into_iter()
+        .map(|path| {
+            let path = Path::new(path)
+                .canonicalize()
+                .map_err(ParseError::FileNotFound)?;
+            Ok(if path.is_dir() {
+                WatchTarget::Dir(path)
+            } else {
+                WatchTarget::File(path)
+            })
+        })
+        .collect()
+}
+
+fn parent_dir(path: &Path) -> PathBuf {
+    path.parent()
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf)
+}
+
+/// True for content changes to a watched file, or to any CSV inside a watched directory.
+fn is_relevant(event: &Event, targets: &[WatchTarget]) -> bool {
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        return false;
+    }
+    event.paths.iter().any(|changed| {
+        targets.iter().any(|target| match target {
+            WatchTarget::File(path) => changed == path,
+            WatchTarget::Dir(dir) => {
+                changed.starts_with(dir)
+                    && changed
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+            }
+        })
+    })
+}
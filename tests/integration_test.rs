@@ -0,0 +1,828 @@
+use goskateapiparser::items::get_item_keywords;
+use goskateapiparser::{
+    AnalysisOutput, AuthorId, ItemAnalysis, ItemName, ItemStats, NumberFormat, OutputFormat,
+    RunConfig, TradeParser, TradeRecord, analyze_trades, merge_outputs, query_sqlite,
+    render_query_result, run_trade_analysis, save_to_sqlite, validate_csv, write_split_output,
+};
+use std::borrow::Cow;
+
+fn fixture_config(name: &str) -> RunConfig {
+    RunConfig {
+        file_paths: vec![format!("tests/fixtures/{}", name)],
+        is_quiet: true,
+        ..RunConfig::default()
+    }
+}
+
+fn find<'a>(analysis: &'a AnalysisOutput, name: &str) -> &'a ItemAnalysis {
+    analysis
+        .items
+        .iter()
+        .find(|item| item.item == name)
+        .unwrap_or_else(|| panic!("{} should be identified", name))
+}
+
+#[test]
+fn normal_file_reports_every_traded_item() {
+    let analysis = analyze_trades(&fixture_config("normal_trades.csv")).unwrap();
+
+    assert_eq!(analysis.metadata.input_files[0].records, 60);
+    assert_eq!(analysis.metadata.observations_count, 55);
+    assert_eq!(analysis.metadata.skipped_records_count, 5);
+    assert_eq!(analysis.items.len(), 3);
+    // The five chat messages name no item and so have no author entry either.
+    assert_eq!(analysis.authors.len(), 55);
+    assert_eq!(analysis.global_volume, 40_400.0);
+    // (495 * 20 + 2000 * 15 + 25 * 20) / 55 posts.
+    assert_eq!(analysis.market_index, Some(734.55));
+    assert_eq!(analysis.index_by_supply_only, Some(338.33));
+    assert_eq!(analysis.index_by_demand_only, Some(1210.0));
+    assert_eq!(analysis.overall_trade_data_span_days, 2.0);
+    // (495 * 20 + 2000 * 15 + 25 * 20) posts over 2 days, scaled to 7.
+    assert_eq!(analysis.estimated_weekly_volume_usd, Some(141_400.0));
+
+    let hot_spring = find(&analysis, "Hot Spring");
+    assert_eq!(hot_spring.estimated_price.median, Some(495.0));
+    assert_eq!(hot_spring.estimated_price.min, Some(400.0));
+    assert_eq!(hot_spring.estimated_price.max, Some(590.0));
+    assert_eq!(hot_spring.supply_demand.supply_posts, 20);
+    assert_eq!(hot_spring.supply_demand.demand_posts, 0);
+    // One post an hour from midnight on the first of June.
+    assert_eq!(
+        hot_spring.first_seen.as_deref(),
+        Some("2025-06-01T00:00:00+00:00")
+    );
+    assert_eq!(
+        hot_spring.last_seen.as_deref(),
+        Some("2025-06-01T19:00:00+00:00")
+    );
+    assert_eq!(hot_spring.observation_span_days, Some(19.0 / 24.0));
+
+    let lowkey = find(&analysis, "Lowkey");
+    assert_eq!(lowkey.estimated_price.median, Some(2000.0));
+    assert_eq!(lowkey.supply_demand.demand_posts, 15);
+
+    let death_squad = find(&analysis, "Death Squad");
+    assert_eq!(death_squad.estimated_price.median, Some(25.0));
+    assert_eq!(death_squad.supply_demand.supply_posts, 10);
+    assert_eq!(death_squad.supply_demand.demand_posts, 10);
+    assert_eq!(death_squad.estimated_trade_chances.chance_to_sell, "50.00%");
+    assert_eq!(death_squad.estimated_trade_chances.chance_to_buy, "50.00%");
+    assert_eq!(death_squad.market_condition, "wide_market");
+}
+
+#[test]
+fn items_display_as_a_one_line_summary() {
+    let analysis = analyze_trades(&fixture_config("normal_trades.csv")).unwrap();
+    let hot_spring = find(&analysis, "Hot Spring");
+
+    assert_eq!(
+        hot_spring.to_string(),
+        "Hot Spring: median=$495 [min=$400, max=$590] supply=20 demand=0 (100.00% sell chance)"
+    );
+    assert_eq!(hot_spring.to_summary_line(), hot_spring.to_string());
+}
+
+#[test]
+fn normal_file_yaml_round_trips() {
+    let yaml = run_trade_analysis(&fixture_config("normal_trades.csv")).unwrap();
+
+    assert!(yaml.starts_with("# Trade Analysis Metadata"));
+    let analysis: AnalysisOutput = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(analysis.items.len(), 3);
+    // Sorted by median price, highest first, when no sort order is given.
+    let names: Vec<&str> = analysis
+        .items
+        .iter()
+        .map(|item| item.item.as_str())
+        .collect();
+    assert_eq!(names, ["Lowkey", "Hot Spring", "Death Squad"]);
+}
+
+#[test]
+fn currency_prefixes_human_readable_prices_only() {
+    let config = RunConfig {
+        output_format: OutputFormat::Markdown,
+        currency: "USD".to_string(),
+        ..fixture_config("million_suffix.csv")
+    };
+    let markdown = run_trade_analysis(&config).unwrap();
+
+    assert!(markdown.contains("| Moldady | $2,500,000.00 | $500,000.00 | $3,000,000.00 |"));
+
+    let analysis = analyze_trades(&config).unwrap();
+    assert_eq!(analysis.currency, "USD");
+    assert_eq!(
+        find(&analysis, "Moldady").estimated_price.median,
+        Some(2_500_000.0)
+    );
+}
+
+#[test]
+fn popularity_rank_orders_by_posts_then_name() {
+    let analysis = analyze_trades(&fixture_config("normal_trades.csv")).unwrap();
+
+    assert_eq!(analysis.total_items_in_dataset, 3);
+    // Death Squad and Hot Spring both have 20 posts, so the name decides.
+    assert_eq!(find(&analysis, "Death Squad").popularity_rank, 1);
+    assert_eq!(find(&analysis, "Hot Spring").popularity_rank, 2);
+    assert_eq!(find(&analysis, "Lowkey").popularity_rank, 3);
+}
+
+#[test]
+fn multi_item_posts_price_each_item_separately() {
+    let analysis = analyze_trades(&RunConfig {
+        include_history: true,
+        ..fixture_config("multi_item.csv")
+    })
+    .unwrap();
+    let prices = |name| -> Vec<f64> {
+        find(&analysis, name)
+            .price_history
+            .iter()
+            .map(|point| point.price)
+            .collect()
+    };
+
+    assert_eq!(analysis.metadata.multi_item_posts_count, 3);
+    assert_eq!(analysis.metadata.observations_count, 7);
+    assert_eq!(prices("Hot Spring"), [3000.0, 1200.0]);
+    assert_eq!(prices("Lowkey"), [800.0, 40.0]);
+    // The stock counts before each item are not prices.
+    assert_eq!(prices("Death Squad"), [500.0]);
+    // A trailing "x2" is a pack size too.
+    assert_eq!(
+        find(&analysis, "Hot Spring").price_history[1].quantity,
+        Some(2)
+    );
+    // A range is one token: its low bound borrows the "k" and both ends go to Catz.
+    assert_eq!(prices("Catz"), [500_000.0, 600_000.0]);
+    assert!(find(&analysis, "Catz").estimated_price.is_range_derived);
+    assert_eq!(prices("Diamonds"), [2000.0]);
+}
+
+#[test]
+fn price_limits_drop_implausible_prices() {
+    let analysis = analyze_trades(&RunConfig {
+        min_price: 405.0,
+        max_price: 500.0,
+        ..fixture_config("normal_trades.csv")
+    })
+    .unwrap();
+
+    assert_eq!(analysis.metadata.price_filtered_count, 45);
+    assert_eq!(analysis.metadata.skipped_by_reason["price_filtered"], 45);
+    assert_eq!(analysis.items.len(), 1);
+    let hot_spring = find(&analysis, "Hot Spring");
+    assert_eq!(hot_spring.estimated_price.min, Some(410.0));
+    assert_eq!(hot_spring.estimated_price.max, Some(500.0));
+}
+
+#[test]
+fn min_price_above_max_price_is_rejected() {
+    let config = RunConfig {
+        min_price: 10.0,
+        max_price: 5.0,
+        ..fixture_config("normal_trades.csv")
+    };
+
+    assert!(analyze_trades(&config).is_err());
+}
+
+#[test]
+fn toml_output_embeds_metadata_as_a_table() {
+    let config = RunConfig {
+        output_format: OutputFormat::Toml,
+        ..fixture_config("single_record.csv")
+    };
+    let text = run_trade_analysis(&config).unwrap();
+    let output: toml::Table = text.parse().unwrap();
+
+    assert_eq!(
+        output["metadata"]["observations_count"].as_integer(),
+        Some(1)
+    );
+    let item = &output["items"][0];
+    assert_eq!(item["item"].as_str(), Some("Hot Spring"));
+    assert_eq!(item["estimated_price"]["median"].as_float(), Some(750.0));
+    // TOML has no null, so a missing variance is simply left out.
+    assert!(item["estimated_price"].get("variance").is_none());
+}
+
+#[test]
+fn records_without_content_yield_no_items() {
+    let analysis = analyze_trades(&fixture_config("missing_content.csv")).unwrap();
+
+    assert_eq!(analysis.metadata.input_files[0].records, 10);
+    assert!(analysis.items.is_empty());
+    assert_eq!(analysis.metadata.skipped_by_reason["missing_content"], 10);
+    assert!(analysis.authors.is_empty());
+    // Only records that became trade observations count towards the time span.
+    assert_eq!(analysis.metadata.earliest_message_utc_epoch, None);
+}
+
+#[test]
+fn unsupported_dates_are_skipped() {
+    let analysis = analyze_trades(&fixture_config("unsupported_dates.csv")).unwrap();
+
+    assert_eq!(analysis.metadata.input_files[0].records, 5);
+    assert!(analysis.items.is_empty());
+    assert_eq!(analysis.metadata.skipped_by_reason["unparseable_date"], 5);
+    assert_eq!(analysis.metadata.earliest_message_utc_epoch, None);
+    assert_eq!(analysis.metadata.csv_data_time_period, "No data available");
+}
+
+#[test]
+fn unsupported_dates_fail_in_strict_mode() {
+    let config = RunConfig {
+        strict: true,
+        ..fixture_config("unsupported_dates.csv")
+    };
+
+    assert!(analyze_trades(&config).is_err());
+}
+
+#[test]
+fn empty_file_yields_empty_analysis() {
+    let analysis = analyze_trades(&fixture_config("empty.csv")).unwrap();
+
+    assert_eq!(analysis.metadata.input_files[0].records, 0);
+    assert!(analysis.items.is_empty());
+    assert_eq!(analysis.global_volume, 0.0);
+    assert_eq!(analysis.market_index, None);
+    assert_eq!(analysis.peak_hour_utc, None);
+}
+
+#[test]
+fn single_record_is_reported_with_insufficient_confidence() {
+    let analysis = analyze_trades(&fixture_config("single_record.csv")).unwrap();
+
+    assert_eq!(analysis.items.len(), 1);
+    let hot_spring = find(&analysis, "Hot Spring");
+    assert_eq!(hot_spring.estimated_price.median, Some(750.0));
+    assert_eq!(hot_spring.estimated_price.variance, None);
+    assert_eq!(hot_spring.supply_demand.supply_posts, 1);
+    assert_eq!(hot_spring.confidence_tier, "insufficient");
+    // A single message spans no time, so there is no weekly rate to scale by.
+    assert_eq!(analysis.estimated_weekly_volume_usd, None);
+}
+
+#[test]
+fn buy_only_file_has_no_sell_side() {
+    let analysis = analyze_trades(&fixture_config("buy_only.csv")).unwrap();
+
+    assert_eq!(analysis.items.len(), 2);
+    for item in &analysis.items {
+        assert_eq!(item.supply_demand.supply_posts, 0);
+        assert_eq!(item.estimated_trade_chances.chance_to_buy, "100.00%");
+        assert_eq!(item.estimated_trade_chances.chance_to_sell, "0.00%");
+        // A spread needs both an ask and a bid.
+        assert_eq!(item.estimated_spread, None);
+        assert_eq!(item.market_condition, "unknown");
+    }
+    let lowkey = find(&analysis, "Lowkey");
+    assert_eq!(lowkey.estimated_price.median, Some(1050.0));
+    assert_eq!(lowkey.estimated_price.mean, Some(1050.0));
+    assert_eq!(lowkey.estimated_price.mean_vs_median_skew, Some(0.0));
+    assert_eq!(lowkey.supply_demand.demand_posts, 4);
+}
+
+#[test]
+fn author_filters_are_or_combined_and_excludes_win() {
+    let config = RunConfig {
+        author_filters: vec!["^trader1$".to_string(), r"^trader2\d$".to_string()],
+        author_excludes: vec!["trader25".to_string()],
+        ..fixture_config("normal_trades.csv")
+    };
+    let analysis = analyze_trades(&config).unwrap();
+
+    assert_eq!(analysis.metadata.author_filtered_count, 50);
+    assert_eq!(analysis.authors.len(), 10);
+    assert_eq!(find(&analysis, "Hot Spring").supply_demand.supply_posts, 1);
+    assert_eq!(find(&analysis, "Lowkey").supply_demand.demand_posts, 9);
+}
+
+#[test]
+fn item_filters_are_or_combined_and_excludes_win() {
+    let config = RunConfig {
+        item_filters: vec!["^Hot".to_string(), "Squad".to_string()],
+        item_excludes: vec!["Death".to_string()],
+        ..fixture_config("normal_trades.csv")
+    };
+    let analysis = analyze_trades(&config).unwrap();
+
+    let names: Vec<&str> = analysis
+        .items
+        .iter()
+        .map(|item| item.item.as_str())
+        .collect();
+    assert_eq!(names, ["Hot Spring"]);
+    assert_eq!(analysis.metadata.items_filtered, 2);
+    // Filtering the output leaves the market-wide figures alone.
+    assert_eq!(analysis.global_volume, 40_400.0);
+}
+
+#[test]
+fn re_exported_records_are_dropped_as_duplicates() {
+    let config = RunConfig {
+        file_paths: vec![
+            "tests/fixtures/normal_trades.csv".to_string(),
+            "tests/fixtures/normal_trades.csv".to_string(),
+        ],
+        ..fixture_config("normal_trades.csv")
+    };
+    let analysis = analyze_trades(&config).unwrap();
+
+    assert_eq!(analysis.metadata.duplicate_records_count, 60);
+    assert_eq!(analysis.metadata.skipped_by_reason["duplicate"], 60);
+    assert_eq!(analysis.metadata.skipped_by_reason["no_item_found"], 5);
+    assert_eq!(find(&analysis, "Hot Spring").supply_demand.supply_posts, 20);
+    assert_eq!(analysis.global_volume, 40_400.0);
+}
+
+#[test]
+fn pack_sizes_give_a_unit_price_and_are_not_read_as_prices() {
+    let analysis = analyze_trades(&fixture_config("packs.csv")).unwrap();
+
+    let hot_spring = find(&analysis, "Hot Spring");
+    assert_eq!(hot_spring.estimated_price.min, Some(40.0));
+    assert_eq!(hot_spring.estimated_price.median, Some(100.0));
+    // 500 / 10, 40 / 1 and 100 / 2.
+    assert_eq!(hot_spring.estimated_price.median_unit_price, Some(50.0));
+}
+
+#[test]
+fn obo_and_firm_posts_set_price_flexibility() {
+    let analysis = analyze_trades(&fixture_config("flexibility.csv")).unwrap();
+
+    assert_eq!(
+        find(&analysis, "Hot Spring").typical_price_flexibility,
+        "mostly_flexible"
+    );
+    assert_eq!(
+        find(&analysis, "Lowkey").typical_price_flexibility,
+        "mostly_firm"
+    );
+    assert_eq!(
+        find(&analysis, "Death Squad").typical_price_flexibility,
+        "unknown"
+    );
+}
+
+#[test]
+fn condition_keywords_split_prices_by_condition() {
+    let analysis = analyze_trades(&fixture_config("conditions.csv")).unwrap();
+
+    let hot_spring = find(&analysis, "Hot Spring");
+    let breakdown: Vec<(&str, u32)> = hot_spring
+        .condition_breakdown
+        .iter()
+        .map(|(condition, posts)| (condition.as_str(), *posts))
+        .collect();
+    assert_eq!(
+        breakdown,
+        [("near_mint", 1), ("new", 2), ("unknown", 1), ("used", 2)]
+    );
+    // 3600 / 6 = 600 against a median of 600.
+    assert_eq!(hot_spring.estimated_price.mean, Some(600.0));
+    assert_eq!(hot_spring.estimated_price.new_median, Some(950.0));
+    assert_eq!(hot_spring.estimated_price.used_median, Some(250.0));
+}
+
+#[test]
+fn split_output_writes_one_file_per_item_and_a_manifest() {
+    let analysis = analyze_trades(&fixture_config("normal_trades.csv")).unwrap();
+    let dir = std::env::temp_dir().join(format!("goskate-split-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let written = write_split_output(&analysis, dir.join("nested").to_str().unwrap()).unwrap();
+
+    assert_eq!(written, 3);
+    let hot_spring = std::fs::read_to_string(dir.join("nested/Hot_Spring.yaml")).unwrap();
+    assert!(hot_spring.starts_with("# Trade Analysis Metadata"));
+    let item: ItemAnalysis = serde_yaml::from_str(&hot_spring).unwrap();
+    assert_eq!(item.estimated_price.median, Some(495.0));
+
+    let manifest: serde_yaml::Value =
+        serde_yaml::from_str(&std::fs::read_to_string(dir.join("nested/manifest.yaml")).unwrap())
+            .unwrap();
+    let files: Vec<&str> = manifest
+        .as_sequence()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["file"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        files,
+        ["Lowkey.yaml", "Hot_Spring.yaml", "Death_Squad.yaml"]
+    );
+    assert_eq!(manifest[2]["median_price"].as_f64(), Some(25.0));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn history_csv_moves_price_histories_out_of_the_main_output() {
+    let dir = std::env::temp_dir().join(format!("goskate-history-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = RunConfig {
+        history_csv: Some(dir.to_str().unwrap().to_string()),
+        output_format: OutputFormat::Json,
+        ..fixture_config("normal_trades.csv")
+    };
+
+    let output: serde_json::Value =
+        serde_json::from_str(&run_trade_analysis(&config).unwrap()).unwrap();
+
+    assert!(
+        output["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|item| item["price_history"].as_array().is_none_or(Vec::is_empty))
+    );
+    let history = std::fs::read_to_string(dir.join("Hot_Spring_history.csv")).unwrap();
+    let lines: Vec<&str> = history.lines().collect();
+    assert_eq!(lines.len(), 21);
+    assert_eq!(lines[0], "date,price,direction,author");
+    assert_eq!(lines[1], "2025-06-01T00:00:00+00:00,400,sell,trader0");
+    assert!(dir.join("Lowkey_history.csv").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sqlite_keeps_one_set_of_rows_per_run() {
+    let analysis = analyze_trades(&fixture_config("normal_trades.csv")).unwrap();
+    let path = std::env::temp_dir().join(format!("goskate-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let first = save_to_sqlite(&analysis, &path).unwrap();
+    let second = save_to_sqlite(&analysis, &path).unwrap();
+    assert!(first < second, "run ids should sort in run order");
+
+    let runs = query_sqlite(
+        &path,
+        "SELECT run_id, COUNT(*) FROM items GROUP BY run_id ORDER BY run_id",
+    )
+    .unwrap();
+    assert_eq!(runs.rows.len(), 2);
+    assert_eq!(runs.rows[0][1], 3);
+    let hot_spring = query_sqlite(
+        &path,
+        &format!(
+            "SELECT item_name, median_price, min_price, max_price, supply_posts, demand_posts \
+             FROM items WHERE run_id = '{}' AND item_name = 'Hot Spring'",
+            second
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        render_query_result(&hot_spring, OutputFormat::Csv).unwrap(),
+        "item_name,median_price,min_price,max_price,supply_posts,demand_posts\n\
+         Hot Spring,495.0,400.0,590.0,20,0"
+    );
+    assert!(query_sqlite(&path, "SELECT * FROM missing").is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn negative_patterns_veto_built_in_keywords() {
+    let analysis = analyze_trades(&fixture_config("negative_patterns.csv")).unwrap();
+
+    // "smooth trade" is chat, not the Smooth deck, so that post prices Hot Spring.
+    assert_eq!(find(&analysis, "Smooth").supply_demand.supply_posts, 1);
+    assert_eq!(
+        find(&analysis, "Hot Spring").estimated_price.median,
+        Some(500.0)
+    );
+    // "nintendo ds" is not Death Squad.
+    assert_eq!(find(&analysis, "Death Squad").supply_demand.supply_posts, 1);
+}
+
+#[test]
+fn built_in_keywords_are_compiled_once_and_shared() {
+    let first = get_item_keywords(None).unwrap();
+    let second = get_item_keywords(None).unwrap();
+
+    assert!(matches!(first, Cow::Borrowed(_)));
+    assert!(std::ptr::eq(&*first, &*second));
+    assert!(first.contains_key("Hot Spring"));
+}
+
+#[test]
+fn items_file_entries_can_carry_negative_patterns() {
+    let config = RunConfig {
+        items_file: Some("tests/fixtures/negative_items.toml".to_string()),
+        ..fixture_config("negative_patterns.csv")
+    };
+    let analysis = analyze_trades(&config).unwrap();
+
+    let element = find(&analysis, "Element");
+    assert_eq!(element.supply_demand.supply_posts, 1);
+    assert_eq!(element.estimated_price.median, Some(70.0));
+}
+
+#[test]
+fn memory_mapped_input_matches_buffered_input() {
+    let config = RunConfig {
+        mmap: true,
+        ..fixture_config("normal_trades.csv")
+    };
+    let mapped = analyze_trades(&config).unwrap();
+    let buffered = analyze_trades(&fixture_config("normal_trades.csv")).unwrap();
+
+    assert_eq!(mapped.metadata.input_files, buffered.metadata.input_files);
+    assert_eq!(mapped.items.len(), buffered.items.len());
+    for (a, b) in mapped.items.iter().zip(&buffered.items) {
+        assert_eq!(a.item, b.item);
+        assert_eq!(a.estimated_price, b.estimated_price);
+        assert_eq!(a.supply_demand, b.supply_demand);
+    }
+}
+
+#[test]
+fn fed_records_match_the_file_analysis() {
+    let config = fixture_config("normal_trades.csv");
+    let mut parser = TradeParser::new(config.clone());
+    let mut reader = csv::Reader::from_path("tests/fixtures/normal_trades.csv").unwrap();
+    for record in reader.deserialize::<TradeRecord>() {
+        parser.feed_record(record.unwrap()).unwrap();
+    }
+    let fed = parser.finalize().unwrap();
+    let parsed = analyze_trades(&config).unwrap();
+
+    assert_eq!(fed.metadata.input_files[0].records, 60);
+    assert_eq!(
+        fed.metadata.observations_count,
+        parsed.metadata.observations_count
+    );
+    assert_eq!(fed.market_index, parsed.market_index);
+    assert_eq!(fed.items.len(), parsed.items.len());
+    for (a, b) in fed.items.iter().zip(&parsed.items) {
+        assert_eq!(a.item, b.item);
+        assert_eq!(a.estimated_price, b.estimated_price);
+        assert_eq!(a.supply_demand, b.supply_demand);
+        assert_eq!(a.popularity_rank, b.popularity_rank);
+    }
+}
+
+#[test]
+fn restored_snapshots_resume_a_fed_run() {
+    let config = fixture_config("normal_trades.csv");
+    let records: Vec<TradeRecord> = csv::Reader::from_path("tests/fixtures/normal_trades.csv")
+        .unwrap()
+        .deserialize()
+        .map(Result::unwrap)
+        .collect();
+    let (first_half, second_half) = records.split_at(records.len() / 2);
+
+    let mut uninterrupted = TradeParser::new(config.clone());
+    for record in &records {
+        uninterrupted.feed_record(record.clone()).unwrap();
+    }
+    let uninterrupted = uninterrupted.finalize().unwrap();
+
+    // Checkpoint halfway, as a pipeline would between invocations.
+    let mut first = TradeParser::new(config.clone());
+    for record in first_half {
+        first.feed_record(record.clone()).unwrap();
+    }
+    let snapshots: Vec<(ItemName, String)> = first
+        .item_stats()
+        .iter()
+        .map(|(name, stats)| (name.clone(), stats.snapshot()))
+        .collect();
+    assert!(!snapshots.is_empty());
+    drop(first);
+
+    let mut resumed = TradeParser::new(config);
+    resumed
+        .restore_item_stats(
+            snapshots
+                .iter()
+                .map(|(name, snapshot)| (name.clone(), ItemStats::restore(snapshot).unwrap())),
+        )
+        .unwrap();
+    for record in second_half {
+        resumed.feed_record(record.clone()).unwrap();
+    }
+    let resumed = resumed.finalize().unwrap();
+
+    assert_eq!(resumed.market_index, uninterrupted.market_index);
+    assert_eq!(
+        resumed.overall_trade_data_span_days,
+        uninterrupted.overall_trade_data_span_days
+    );
+    assert_eq!(resumed.items.len(), uninterrupted.items.len());
+    for (a, b) in resumed.items.iter().zip(&uninterrupted.items) {
+        assert_eq!(a.item, b.item);
+        assert_eq!(a.estimated_price, b.estimated_price);
+        assert_eq!(a.supply_demand, b.supply_demand);
+        assert_eq!(a.rough_selling_frequency, b.rough_selling_frequency);
+        assert_eq!(a.dominant_price, b.dominant_price);
+        assert_eq!(a.popularity_rank, b.popularity_rank);
+    }
+}
+
+fn fed_post(author_id: u64, author: &str, hour: u32, content: &str) -> TradeRecord {
+    TradeRecord {
+        author_id: AuthorId(author_id),
+        author: author.to_string(),
+        date: format!("2025-06-01T{:02}:00:00+00:00", hour),
+        content: Some(content.to_string()),
+        attachments: None,
+        reactions: None,
+    }
+}
+
+#[test]
+fn only_posts_keeping_several_items_count_as_multi_item() {
+    let config = RunConfig {
+        is_quiet: true,
+        max_price: 1000.0,
+        item_aliases: [("Lowkey".to_string(), "Hot Spring".to_string())].into(),
+        ..RunConfig::default()
+    };
+    let mut parser = TradeParser::new(config);
+    for record in [
+        // Both names fold into Hot Spring, which is observed once.
+        fed_post(1, "rider", 1, "selling hot spring 900 and lowkey 800"),
+        // The Hot Spring price is over --max-price, leaving one item.
+        fed_post(2, "shop", 2, "selling hot spring 3000 and death squad 500"),
+        fed_post(
+            3,
+            "collector",
+            3,
+            "selling hot spring 700 and death squad 400",
+        ),
+    ] {
+        parser.feed_record(record).unwrap();
+    }
+    let analysis = parser.finalize().unwrap();
+
+    assert_eq!(analysis.metadata.multi_item_posts_count, 1);
+    assert_eq!(analysis.metadata.observations_count, 4);
+    assert_eq!(find(&analysis, "Hot Spring").supply_demand.supply_posts, 2);
+}
+
+#[test]
+fn top_sellers_and_buyers_rank_authors_by_posts() {
+    let mut parser = TradeParser::new(RunConfig {
+        is_quiet: true,
+        ..RunConfig::default()
+    });
+    for record in [
+        fed_post(1, "shop", 0, "selling Hot Spring 400"),
+        fed_post(1, "shop", 1, "selling Lowkey 30"),
+        fed_post(2, "rider", 2, "selling Hot Spring 450"),
+        fed_post(3, "collector", 3, "selling Death Squad 2000"),
+        fed_post(3, "collector", 4, "buying Hot Spring 380"),
+        fed_post(3, "collector", 5, "buying Lowkey 20"),
+        fed_post(4, "newbie", 6, "buying Lowkey 25"),
+    ] {
+        parser.feed_record(record).unwrap();
+    }
+    let analysis = parser.finalize().unwrap();
+
+    let sellers = analysis.top_sellers(2);
+    let names: Vec<&str> = sellers.iter().map(|a| a.author.as_str()).collect();
+    // rider and collector tie on one sale each; collector listed more value.
+    assert_eq!(names, ["shop", "collector"]);
+    assert_eq!(sellers[0].total_value_listed, 430.0);
+    assert_eq!(sellers[1].total_value_listed, 2400.0);
+
+    let buyers = analysis.top_buyers(10);
+    let names: Vec<&str> = buyers.iter().map(|a| a.author.as_str()).collect();
+    assert_eq!(names, ["collector", "newbie"]);
+}
+
+#[test]
+fn validate_passes_a_well_formed_export() {
+    let report = validate_csv("tests/fixtures/normal_trades.csv", None).unwrap();
+
+    assert!(report.is_valid());
+    assert_eq!(report.rows_checked, 60);
+}
+
+#[test]
+fn validate_reports_missing_columns_and_bad_rows() {
+    let report = validate_csv("tests/fixtures/missing_columns.csv", None).unwrap();
+    assert!(!report.is_valid());
+    assert_eq!(report.missing_columns, ["Date", "Attachments", "Reactions"]);
+    assert_eq!(report.rows_checked, 0);
+
+    let report = validate_csv("tests/fixtures/unsupported_dates.csv", None).unwrap();
+    assert!(report.missing_columns.is_empty());
+    let lines: Vec<usize> = report.row_errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, [2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn merging_saved_runs_matches_parsing_the_exports_together() {
+    let with_history = |files: &[&str]| RunConfig {
+        file_paths: files
+            .iter()
+            .map(|name| format!("tests/fixtures/{}", name))
+            .collect(),
+        include_history: true,
+        ..fixture_config("")
+    };
+    let a = analyze_trades(&with_history(&["normal_trades.csv"])).unwrap();
+    let b = analyze_trades(&with_history(&["conditions.csv"])).unwrap();
+    let together = analyze_trades(&with_history(&["normal_trades.csv", "conditions.csv"])).unwrap();
+
+    let merged = merge_outputs(&a, &b, &with_history(&[])).unwrap();
+
+    assert_eq!(
+        merged.total_parsing_time_ms,
+        a.total_parsing_time_ms + b.total_parsing_time_ms
+    );
+    assert_eq!(merged.global_volume, together.global_volume);
+    assert_eq!(merged.market_index, together.market_index);
+    assert_eq!(merged.authors.len(), together.authors.len());
+    assert_eq!(merged.items.len(), together.items.len());
+    for item in &together.items {
+        let merged_item = find(&merged, &item.item);
+        assert_eq!(merged_item.estimated_price, item.estimated_price);
+        assert_eq!(merged_item.supply_demand, item.supply_demand);
+        assert_eq!(merged_item.condition_breakdown, item.condition_breakdown);
+        assert_eq!(merged_item.first_seen, item.first_seen);
+        assert_eq!(merged_item.last_seen, item.last_seen);
+    }
+}
+
+#[test]
+fn merging_needs_price_history() {
+    let a = analyze_trades(&fixture_config("single_record.csv")).unwrap();
+    let b = analyze_trades(&fixture_config("buy_only.csv")).unwrap();
+
+    assert!(merge_outputs(&a, &b, &RunConfig::default()).is_err());
+}
+
+fn history_prices(config: &RunConfig) -> Vec<f64> {
+    let analysis = analyze_trades(&RunConfig {
+        include_history: true,
+        ..config.clone()
+    })
+    .unwrap();
+    find(&analysis, "Hot Spring")
+        .price_history
+        .iter()
+        .map(|point| point.price)
+        .collect()
+}
+
+#[test]
+fn european_number_format_swaps_the_separators() {
+    let config = RunConfig {
+        number_format: NumberFormat::European,
+        ..fixture_config("european_prices.csv")
+    };
+
+    assert_eq!(
+        history_prices(&config),
+        vec![1234.5, 1200.0, 1100.0, 1300.0, 2500.0]
+    );
+}
+
+#[test]
+fn auto_number_format_detects_european_prices() {
+    let european = RunConfig {
+        number_format: NumberFormat::European,
+        ..fixture_config("european_prices.csv")
+    };
+    let auto = RunConfig {
+        number_format: NumberFormat::Auto,
+        ..fixture_config("european_prices.csv")
+    };
+    let auto_us = RunConfig {
+        number_format: NumberFormat::Auto,
+        ..fixture_config("normal_trades.csv")
+    };
+
+    assert_eq!(history_prices(&auto), history_prices(&european));
+    assert_eq!(
+        history_prices(&auto_us),
+        history_prices(&fixture_config("normal_trades.csv"))
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_analysis_matches_the_blocking_one() {
+    let config = fixture_config("normal_trades.csv");
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let analysis = runtime
+        .block_on(goskateapiparser::run_trade_analysis_async(config.clone()))
+        .unwrap();
+    let blocking = analyze_trades(&config).unwrap();
+
+    assert_eq!(analysis.metadata.input_files, blocking.metadata.input_files);
+    assert_eq!(analysis.market_index, blocking.market_index);
+    assert_eq!(analysis.items.len(), blocking.items.len());
+    for (a, b) in analysis.items.iter().zip(&blocking.items) {
+        assert_eq!(a.item, b.item);
+        assert_eq!(a.estimated_price, b.estimated_price);
+    }
+}
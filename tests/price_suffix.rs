@@ -0,0 +1,19 @@
+use goskateapiparser::{RunConfig, analyze_trades};
+
+#[test]
+fn million_suffix_prices_are_scaled() {
+    let config = RunConfig {
+        file_paths: vec!["tests/fixtures/million_suffix.csv".to_string()],
+        ..RunConfig::default()
+    };
+    let analysis = analyze_trades(&config).unwrap();
+    let moldady = analysis
+        .items
+        .iter()
+        .find(|item| item.item == "Moldady")
+        .expect("Moldady should be identified");
+
+    assert_eq!(moldady.estimated_price.min, Some(500_000.0));
+    assert_eq!(moldady.estimated_price.median, Some(2_500_000.0));
+    assert_eq!(moldady.estimated_price.max, Some(3_000_000.0));
+}